@@ -1,38 +1,499 @@
-use crate::{models, TimeCode};
-use std::collections::HashSet;
+use crate::{index::RoomIndex, models, TimeCode};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// An extension trait to add finding course in room functionality
 pub trait FindCourseInRoomAtTime {
     /// Find a course in a room at a given time instant.
+    ///
+    /// If a date is given, periods that are not held on that date are ignored.
     fn find_course_in_room_at_time(
         &self,
         room: &str,
         time: TimeCode,
         day: models::Day,
+        date: Option<models::CalendarDate>,
     ) -> Vec<models::Course> {
-        self.find_course_in_room_at_time_range(room, time, time, day)
+        self.find_course_in_room_at_time_range(room, time, time, day, date)
     }
-    /// Find a course in a room for a given range
+    /// Find a course in a room for a given range.
+    ///
+    /// Ranges include their start but not their end, so a period ending at 11:50 is not held
+    /// during a range starting at 11:50. A range starting and ending together is an instant.
+    ///
+    /// Each returned course only contains the sections and periods that actually meet in the room
+    /// during the range.
     fn find_course_in_room_at_time_range(
         &self,
         room: &str,
         time_start: TimeCode,
         time_end: TimeCode,
         day: models::Day,
+        date: Option<models::CalendarDate>,
     ) -> Vec<models::Course>;
 }
 
 /// A trait that allows for finding empty rooms for a given time range
 pub trait FindEmptyRooms {
-    /// Find empty rooms given a start time, and ending time, and a day
+    /// Find empty rooms given a start time, and ending time, and a day.
+    ///
+    /// Like courses in a room, ranges include their start but not their end. If a date is given,
+    /// periods that are not held on that date are ignored.
     fn find_empty_rooms(
         &self,
         time_start: TimeCode,
         time_end: TimeCode,
         day: models::Day,
+        date: Option<models::CalendarDate>,
     ) -> Vec<String>;
+
+    /// Find empty rooms like [`find_empty_rooms`](FindEmptyRooms::find_empty_rooms), along with
+    /// the periods held in each room directly before and after the range.
+    fn find_empty_rooms_with_neighbours(
+        &self,
+        time_start: TimeCode,
+        time_end: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<EmptyRoom>;
+}
+
+/// A trait that allows for finding when rooms are free during a day
+pub trait FindFreeSlots {
+    /// Find every interval of at least `min_length` minutes between `earliest` and `latest` on a
+    /// day during which a room is free, sorted by room and then by time. Use
+    /// [`FreeSlot::minutes`] to sort them by length instead.
+    ///
+    /// If a date is given, periods that are not held on that date are ignored.
+    fn find_free_slots(
+        &self,
+        earliest: TimeCode,
+        latest: TimeCode,
+        min_length: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<FreeSlot>;
+}
+
+/// A trait that allows for listing the courses that match a [`CourseFilter`]
+pub trait FilterCourses {
+    /// Find every course matching the filter.
+    ///
+    /// Each returned course only contains the sections that match the filter.
+    fn filter_courses(&self, filter: &CourseFilter) -> Vec<models::Course>;
+
+    /// Find the rooms used by sections held on a campus, or `None` if no section has a known
+    /// campus, as in course DBs parsed from RCOS XML.
+    fn rooms_on_campus(&self, campus: &models::Campus) -> Option<BTreeSet<&str>>;
+}
+
+/// Criteria that courses and sections must all meet to be listed. Unset criteria match anything.
+#[derive(Debug, Clone, Default)]
+pub struct CourseFilter {
+    /// The department of the course, ignoring case
+    pub dept: Option<String>,
+    /// A number of credit hours the course can be taken for
+    pub credits: Option<u8>,
+    /// How the course is graded
+    pub grade_type: Option<models::GradeType>,
+    /// The campus the section is held on
+    pub campus: Option<models::Campus>,
+    /// An attribute of the section, by name or initials
+    pub attribute: Option<String>,
+    /// A day the section meets on
+    pub day: Option<models::Day>,
+    /// The earliest time a meeting of the section may start
+    pub starts_after: Option<TimeCode>,
+    /// The latest time a meeting of the section may end
+    pub ends_before: Option<TimeCode>,
+}
+
+impl CourseFilter {
+    /// Whether the course meets every criterion of the filter, ignoring criteria on sections.
+    /// Courses that are missing the information a criterion needs do not meet it.
+    pub fn matches_course(&self, course: &models::Course) -> bool {
+        let dept = self
+            .dept
+            .as_ref()
+            .is_none_or(|dept| course.dept.eq_ignore_ascii_case(dept));
+        let credits = self
+            .credits
+            .is_none_or(|credits| course.credits.is_some_and(|range| range.contains(credits)));
+        let grade_type = self
+            .grade_type
+            .as_ref()
+            .is_none_or(|grade_type| course.grade_type.as_ref() == Some(grade_type));
+        dept && credits && grade_type
+    }
+
+    /// Whether the section meets every criterion of the filter on sections.
+    pub fn matches_section(&self, section: &models::Section) -> bool {
+        let campus = self
+            .campus
+            .as_ref()
+            .is_none_or(|campus| section.campus.as_ref() == Some(campus));
+        let attribute = self
+            .attribute
+            .as_ref()
+            .is_none_or(|attribute| section.has_attribute(attribute));
+        let meets =
+            (self.day.is_none() && self.starts_after.is_none() && self.ends_before.is_none())
+                || section
+                    .periods
+                    .iter()
+                    .any(|period| self.matches_period(period));
+        campus && attribute && meets
+    }
+
+    fn matches_period(&self, period: &models::Period) -> bool {
+        self.day.is_none_or(|day| period.days.contains(&day))
+            && self.starts_after.is_none_or(|starts_after| {
+                period.time.is_some_and(|time| time.start >= starts_after)
+            })
+            && self
+                .ends_before
+                .is_none_or(|ends_before| period.time.is_some_and(|time| time.end <= ends_before))
+    }
+}
+
+/// A trait that allows for finding where a course meets
+pub trait FindClass {
+    /// Find every course matching the query. If no course has the department and number asked
+    /// for, they are searched for as words of titles instead.
+    ///
+    /// Each returned course only contains the sections that match the query.
+    fn find_class(&self, query: &CourseQuery) -> Vec<models::Course>;
+
+    /// Find, for every section matching the query, the meeting in progress at a moment, or else
+    /// the next one within a week.
+    ///
+    /// If the date of `day` is given, periods are ignored on the days they are not held. Dates are
+    /// counted in `year`, see [`CalendarDate::add_days`](models::CalendarDate::add_days).
+    fn find_next_meetings(
+        &self,
+        query: &CourseQuery,
+        time: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+        year: i32,
+    ) -> Vec<Meeting> {
+        self.find_class(query)
+            .into_iter()
+            .flat_map(|course| {
+                course
+                    .sections
+                    .iter()
+                    .filter_map(|section| {
+                        let periods = section.periods.iter().collect::<Vec<_>>();
+                        let (idx, day, in_progress) =
+                            next_meeting(&periods, time, day, date, year)?;
+                        Some(Meeting {
+                            course: models::Course {
+                                sections: vec![models::Section {
+                                    periods: vec![periods[idx].clone()],
+                                    ..section.clone()
+                                }],
+                                ..course.clone()
+                            },
+                            day,
+                            in_progress,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// The index of the period in progress at a moment, or else of the next one to start within a
+/// week, along with the day it is held on and whether it is in progress. The date, if any, is
+/// that of `day`, and is moved along with the days that are checked.
+fn next_meeting(
+    periods: &[&models::Period],
+    time: TimeCode,
+    day: models::Day,
+    date: Option<models::CalendarDate>,
+    year: i32,
+) -> Option<(usize, models::Day, bool)> {
+    // The same day a week later comes last, for periods that already ended that day
+    (0..=7).find_map(|offset| {
+        let on = models::Day::ALL[(day as usize + offset) % 7];
+        let date = date.map(|date| date.add_days(offset as u64, year));
+        periods
+            .iter()
+            .enumerate()
+            .filter(|(_, period)| period.meets_on(on, date))
+            .filter_map(|(idx, period)| period.time.map(|range| (idx, range)))
+            .filter(|(_, range)| offset > 0 || range.end > time)
+            .min_by_key(|(_, range)| range.start)
+            .map(|(idx, range)| (idx, on, offset == 0 && range.start <= time))
+    })
+}
+
+/// A way to pick out a course or section, from the words a user would type
+#[derive(Debug, Clone, PartialEq)]
+pub enum CourseQuery {
+    /// A department and course number, like "CSCI 1200" or "csci-1200"
+    Course { dept: String, num: u16 },
+    /// The CRN of a single section
+    Crn(u32),
+    /// Words from the title of a course, like "data struct"
+    Title(String),
+}
+
+impl From<&str> for CourseQuery {
+    fn from(from: &str) -> Self {
+        let query = from.trim();
+        if let Ok(crn) = query.parse() {
+            return Self::Crn(crn);
+        }
+        let (dept, num) = query.split_at(
+            query
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(query.len()),
+        );
+        let num = num.trim_start_matches([' ', '-']);
+        match num.parse() {
+            Ok(number) if !dept.is_empty() && num.bytes().all(|byte| byte.is_ascii_digit()) => {
+                Self::Course {
+                    dept: dept.to_ascii_uppercase(),
+                    num: number,
+                }
+            }
+            _ => Self::Title(query.to_string()),
+        }
+    }
+}
+
+impl CourseQuery {
+    /// Whether the course is the one asked for, or its title matches the words of the query.
+    pub fn matches_course(&self, course: &models::Course) -> bool {
+        match self {
+            Self::Course { dept, num } => {
+                course.dept.eq_ignore_ascii_case(dept) && course.num == *num
+            }
+            Self::Crn(_) => true,
+            Self::Title(title) => fuzzy_matches(title, &course.name),
+        }
+    }
+
+    /// The title search to try when no course is the one asked for, as titles may end in a number
+    /// too, like "STRUCTURES 1"
+    fn title_fallback(&self) -> Option<CourseQuery> {
+        match self {
+            Self::Course { dept, num } => Some(Self::Title(format!("{} {}", dept, num))),
+            _ => None,
+        }
+    }
+
+    /// Whether the section is the one asked for. Queries for whole courses match every section.
+    pub fn matches_section(&self, section: &models::Section) -> bool {
+        match self {
+            Self::Crn(crn) => section.crn == *crn,
+            _ => true,
+        }
+    }
+}
+
+/// Whether every word of the query starts a word of the text, ignoring case and punctuation, so
+/// that "data struct" matches "DATA STRUCTURES".
+pub fn fuzzy_matches(query: &str, text: &str) -> bool {
+    let words = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    let query = words(query);
+    let text = words(text);
+    !query.is_empty()
+        && query.iter().all(|word| {
+            text.iter()
+                .any(|text_word| text_word.starts_with(word.as_str()))
+        })
+}
+
+/// A trait that allows for looking up what instructors teach
+pub trait FindInstructors {
+    /// Find every instructor whose name matches, see [`fuzzy_matches`], along with the periods
+    /// they teach. Instructors are sorted by name.
+    fn find_instructors(&self, name: &str) -> Vec<InstructorSchedule>;
+}
+
+/// An instructor, along with the periods they teach
+#[derive(Clone, PartialEq, Debug)]
+pub struct InstructorSchedule {
+    /// The name of the instructor
+    pub name: String,
+
+    /// The courses the instructor teaches, with only their sections and periods
+    pub courses: Vec<models::Course>,
+}
+
+impl InstructorSchedule {
+    /// Every period the instructor teaches, with its course and section, once for each day it is
+    /// held on. Periods are sorted by day and then by time, and TBA periods are left out.
+    pub fn timetable(
+        &self,
+    ) -> Vec<(
+        models::Day,
+        &models::Course,
+        &models::Section,
+        &models::Period,
+    )> {
+        let mut timetable = self
+            .periods()
+            .flat_map(|((course, section), period)| {
+                period
+                    .days
+                    .iter()
+                    .filter(|_| period.time.is_some())
+                    .map(move |day| (*day, course, section, period))
+            })
+            .collect::<Vec<_>>();
+        timetable
+            .sort_by_key(|(day, _, _, period)| (*day as usize, period.time.map(|time| time.start)));
+        timetable
+    }
+
+    /// The meeting the instructor is teaching at a moment, or else the next one within a week.
+    ///
+    /// If the date of `day` is given, periods are ignored on the days they are not held. Dates are
+    /// counted in `year`, see [`CalendarDate::add_days`](models::CalendarDate::add_days).
+    pub fn next_meeting(
+        &self,
+        time: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+        year: i32,
+    ) -> Option<Meeting> {
+        let periods = self.periods().collect::<Vec<_>>();
+        let (idx, day, in_progress) = next_meeting(
+            &periods
+                .iter()
+                .map(|(_, period)| *period)
+                .collect::<Vec<_>>(),
+            time,
+            day,
+            date,
+            year,
+        )?;
+        let ((course, section), period) = periods[idx];
+        Some(Meeting {
+            course: models::Course {
+                sections: vec![models::Section {
+                    periods: vec![period.clone()],
+                    ..section.clone()
+                }],
+                ..course.clone()
+            },
+            day,
+            in_progress,
+        })
+    }
+
+    fn periods(
+        &self,
+    ) -> impl Iterator<Item = ((&models::Course, &models::Section), &models::Period)> {
+        self.courses.iter().flat_map(|course| {
+            course.sections.iter().flat_map(move |section| {
+                section
+                    .periods
+                    .iter()
+                    .map(move |period| ((course, section), period))
+            })
+        })
+    }
+}
+
+/// A trait that allows for finding the sections a section is crosslisted with
+pub trait FindCrosslistings {
+    /// Find the crosslisting group containing the given CRN, if any
+    fn find_crosslisting(&self, crn: u32) -> Option<&models::Crosslisting>;
+
+    /// Find the CRNs of every section crosslisted with the given CRN, not including itself
+    fn find_crosslisted_siblings(&self, crn: u32) -> Vec<u32> {
+        self.find_crosslisting(crn)
+            .map(|crosslisting| {
+                crosslisting
+                    .crns
+                    .iter()
+                    .copied()
+                    .filter(|sibling| *sibling != crn)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A trait that allows for listing the sections that are not fully scheduled yet
+pub trait FindUnscheduled {
+    /// Find every section without periods, or with a period that is missing its time, its days or
+    /// its room.
+    ///
+    /// Each returned course only contains those sections, with all of their periods.
+    fn find_unscheduled(&self) -> Vec<models::Course>;
+}
+
+/// A room that is empty for a queried time range
+#[derive(Clone, PartialEq, Debug)]
+pub struct EmptyRoom {
+    /// The SIS room name
+    pub room: String,
+
+    /// The last booking in the room that ends before the range, if any
+    pub previous: Option<RoomBooking>,
+
+    /// The first booking in the room that starts after the range, if any
+    pub next: Option<RoomBooking>,
+}
+
+/// An interval during which a room is free
+#[derive(Clone, PartialEq, Debug)]
+pub struct FreeSlot {
+    /// The SIS room name
+    pub room: String,
+
+    /// When the room is free
+    pub time: models::TimeRange,
 }
 
+impl FreeSlot {
+    /// The length of the slot, in minutes
+    pub fn minutes(&self) -> u16 { self.time.minutes() }
+}
+
+/// A meeting of a section, in progress or coming up
+#[derive(Clone, PartialEq, Debug)]
+pub struct Meeting {
+    /// The course, with only the section and the period that meets
+    pub course: models::Course,
+
+    /// The day of the meeting
+    pub day: models::Day,
+
+    /// Whether the meeting is in progress, rather than coming up next
+    pub in_progress: bool,
+}
+
+/// A single period that occupies a room, along with the course it belongs to
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoomBooking {
+    /// The department of the course (e.g. CSCI)
+    pub dept: String,
+
+    /// The number of the course
+    pub num: u16,
+
+    /// When the period occupies the room
+    pub time: models::TimeRange,
+
+    /// The period occupying the room
+    pub period: models::Period,
+}
+
+// Queries on the course DB build a throwaway index. To run several queries, build a
+// `RoomIndex` once and query it directly instead, which can also keep passing time free between
+// periods.
 impl FindCourseInRoomAtTime for models::CourseDB {
     fn find_course_in_room_at_time_range(
         &self,
@@ -40,29 +501,10 @@ impl FindCourseInRoomAtTime for models::CourseDB {
         time_start: TimeCode,
         time_end: TimeCode,
         day: models::Day,
+        date: Option<models::CalendarDate>,
     ) -> Vec<models::Course> {
-        let mut clash = Vec::new();
-        // Naive impl b/c lazy (whats dp lol)
-        for course in &self.courses {
-            for section in &course.sections {
-                for period in &section.periods {
-                    if period.location.is_some() {
-                        let loc = period.location.as_ref().unwrap();
-                        let time_start_between =
-                            period.time_start <= time_start && period.time_end >= time_start;
-                        let time_end_between =
-                            period.time_start <= time_end && period.time_end >= time_end;
-                        let time_covers =
-                            time_start <= period.time_start && time_end >= period.time_end;
-                        let conflict = time_start_between || time_end_between || time_covers;
-                        if conflict && loc.as_str() == room && period.days.contains(&day) {
-                            clash.push(course.clone());
-                        }
-                    }
-                }
-            }
-        }
-        clash
+        RoomIndex::new(self)
+            .find_course_in_room_at_time_range(room, time_start, time_end, day, date)
     }
 }
 
@@ -72,27 +514,417 @@ impl FindEmptyRooms for models::CourseDB {
         time_start: TimeCode,
         time_end: TimeCode,
         day: models::Day,
+        date: Option<models::CalendarDate>,
     ) -> Vec<String> {
-        let mut rooms = HashSet::new();
-        for course in &self.courses {
+        RoomIndex::new(self).find_empty_rooms(time_start, time_end, day, date)
+    }
+
+    fn find_empty_rooms_with_neighbours(
+        &self,
+        time_start: TimeCode,
+        time_end: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<EmptyRoom> {
+        RoomIndex::new(self).find_empty_rooms_with_neighbours(time_start, time_end, day, date)
+    }
+}
+
+impl FindFreeSlots for models::CourseDB {
+    fn find_free_slots(
+        &self,
+        earliest: TimeCode,
+        latest: TimeCode,
+        min_length: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<FreeSlot> {
+        RoomIndex::new(self).find_free_slots(earliest, latest, min_length, day, date)
+    }
+}
+
+impl FindCrosslistings for models::CourseDB {
+    fn find_crosslisting(&self, crn: u32) -> Option<&models::Crosslisting> {
+        self.crosslistings
+            .iter()
+            .find(|crosslisting| crosslisting.crns.contains(&crn))
+    }
+}
+
+impl FilterCourses for models::CourseDB {
+    fn filter_courses(&self, filter: &CourseFilter) -> Vec<models::Course> {
+        self.courses
+            .iter()
+            .filter(|course| filter.matches_course(course))
+            .filter_map(|course| {
+                let sections = course
+                    .sections
+                    .iter()
+                    .filter(|section| filter.matches_section(section))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if sections.is_empty() {
+                    None
+                } else {
+                    Some(models::Course {
+                        sections,
+                        ..course.clone()
+                    })
+                }
+            })
+            .collect()
+    }
+
+    fn rooms_on_campus(&self, campus: &models::Campus) -> Option<BTreeSet<&str>> {
+        let sections = self
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter());
+        if sections.clone().all(|section| section.campus.is_none()) {
+            return None;
+        }
+        let rooms = sections
+            .filter(|section| section.campus.as_ref() == Some(campus))
+            .flat_map(|section| section.periods.iter())
+            .filter_map(|period| period.location.as_deref())
+            .collect();
+        Some(rooms)
+    }
+}
+
+impl FindClass for models::CourseDB {
+    fn find_class(&self, query: &CourseQuery) -> Vec<models::Course> {
+        let find = |query: &CourseQuery| {
+            self.courses
+                .iter()
+                .filter(|course| query.matches_course(course))
+                .filter_map(|course| {
+                    let sections = course
+                        .sections
+                        .iter()
+                        .filter(|section| query.matches_section(section))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if sections.is_empty() {
+                        None
+                    } else {
+                        Some(models::Course {
+                            sections,
+                            ..course.clone()
+                        })
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let courses = find(query);
+        match query.title_fallback() {
+            Some(title) if courses.is_empty() => find(&title),
+            _ => courses,
+        }
+    }
+}
+
+impl FindInstructors for models::CourseDB {
+    fn find_instructors(&self, name: &str) -> Vec<InstructorSchedule> {
+        // The courses of each instructor, and which course each of their CRNs was taken from
+        let mut schedules = BTreeMap::<&str, (Vec<models::Course>, BTreeMap<u32, usize>)>::new();
+        for (course_idx, course) in self.courses.iter().enumerate() {
             for section in &course.sections {
                 for period in &section.periods {
-                    if let Some(loc) = period.location.as_ref() {
-                        rooms.insert(loc);
+                    for instructor in &period.instructors {
+                        if !fuzzy_matches(name, &instructor.name) {
+                            continue;
+                        }
+                        let (courses, crns) = schedules.entry(&instructor.name).or_default();
+                        // Some course DBs list a section under several copies of its course
+                        if *crns.entry(section.crn).or_insert(course_idx) != course_idx {
+                            continue;
+                        }
+                        if courses.last().is_none_or(|last| {
+                            (&last.dept, last.num, &last.name)
+                                != (&course.dept, course.num, &course.name)
+                        }) {
+                            courses.push(models::Course {
+                                sections: vec![],
+                                ..course.clone()
+                            });
+                        }
+                        let sections = &mut courses.last_mut().unwrap().sections;
+                        if sections.last().is_none_or(|last| last.crn != section.crn) {
+                            sections.push(models::Section {
+                                periods: vec![],
+                                ..section.clone()
+                            });
+                        }
+                        let periods = &mut sections.last_mut().unwrap().periods;
+                        if !periods.contains(period) {
+                            periods.push(period.clone());
+                        }
                     }
                 }
             }
         }
-        let mut valid = rooms
+        schedules
+            .into_iter()
+            .map(|(name, (courses, _))| InstructorSchedule {
+                name: name.to_string(),
+                courses,
+            })
+            .collect()
+    }
+}
+
+impl FindUnscheduled for models::CourseDB {
+    fn find_unscheduled(&self) -> Vec<models::Course> {
+        self.courses
+            .iter()
+            .filter_map(|course| {
+                let sections = course
+                    .sections
+                    .iter()
+                    .filter(|section| {
+                        section.periods.is_empty()
+                            || section
+                                .periods
+                                .iter()
+                                .any(|period| !period.is_scheduled() || period.location.is_none())
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if sections.is_empty() {
+                    None
+                } else {
+                    Some(models::Course {
+                        sections,
+                        ..course.clone()
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Day, schema};
+
+    #[test]
+    fn rooms_on_campus_need_campus_data() {
+        let mut db = schema::bundled_db("201909.json").0;
+        assert_eq!(db.rooms_on_campus(&models::Campus::Troy), None);
+        let section = db.courses[0]
+            .sections
+            .iter_mut()
+            .find(|section| {
+                section
+                    .periods
+                    .iter()
+                    .any(|period| period.location.is_some())
+            })
+            .unwrap();
+        section.campus = Some(models::Campus::Troy);
+        let room = section.periods[0].location.clone().unwrap();
+        let rooms = db.rooms_on_campus(&models::Campus::Troy).unwrap();
+        assert!(rooms.contains(room.as_str()));
+        assert!(db
+            .rooms_on_campus(&models::Campus::Other("Hartford".to_string()))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn queries_are_read_like_users_type_them() {
+        let course = CourseQuery::Course {
+            dept: "CSCI".to_string(),
+            num: 1200,
+        };
+        assert_eq!(CourseQuery::from("CSCI 1200"), course);
+        assert_eq!(CourseQuery::from(" csci-1200 "), course);
+        assert_eq!(CourseQuery::from("CSCI1200"), course);
+        assert_eq!(CourseQuery::from("81047"), CourseQuery::Crn(81047));
+        assert_eq!(
+            CourseQuery::from("data struct"),
+            CourseQuery::Title("data struct".to_string())
+        );
+        assert_eq!(
+            CourseQuery::from("CSCI +1200"),
+            CourseQuery::Title("CSCI +1200".to_string())
+        );
+    }
+
+    #[test]
+    fn titles_match_by_word_prefixes() {
+        assert!(fuzzy_matches("data struct", "DATA STRUCTURES"));
+        assert!(fuzzy_matches("Struct", "DATA STRUCTURES"));
+        assert!(fuzzy_matches("intro prog", "INTRO TO PROGRAMMING"));
+        assert!(!fuzzy_matches("ata", "DATA STRUCTURES"));
+        assert!(!fuzzy_matches("data algorithms", "DATA STRUCTURES"));
+        assert!(!fuzzy_matches("", "DATA STRUCTURES"));
+    }
+
+    #[test]
+    fn classes_are_found_by_course_crn_or_title() {
+        let db = schema::bundled_db("201909.json").0;
+        let courses = db.find_class(&CourseQuery::from("csci 1200"));
+        assert_eq!(courses.len(), 1);
+        assert_eq!(courses[0].sections.len(), 10);
+        let courses = db.find_class(&CourseQuery::from("81047"));
+        assert_eq!(courses.len(), 1);
+        assert_eq!(courses[0].num, 1200);
+        assert_eq!(
+            courses[0]
+                .sections
+                .iter()
+                .map(|section| section.crn)
+                .collect::<Vec<_>>(),
+            [81047]
+        );
+        assert!(db
+            .find_class(&CourseQuery::from("data struct"))
             .iter()
-            .filter(|room| {
-                self.find_course_in_room_at_time_range(room, time_start, time_end, day)
-                    .len()
-                    == 0
+            .any(|course| course.dept == "CSCI" && course.num == 1200));
+        assert!(db.find_class(&CourseQuery::from("CSCI 9999")).is_empty());
+    }
+
+    #[test]
+    fn titles_ending_in_numbers_are_found() {
+        let db = schema::bundled_db("201909.json").0;
+        let query = CourseQuery::from("structures 1");
+        assert!(matches!(query, CourseQuery::Course { .. }));
+        let names = db
+            .find_class(&query)
+            .into_iter()
+            .map(|course| course.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["STRUCTURES 1"]);
+    }
+
+    #[test]
+    fn next_meetings_wrap_around_the_week() {
+        let db = schema::bundled_db("201909.json").0;
+        let query = CourseQuery::Crn(81047);
+        let next = |time, day| {
+            let meetings =
+                db.find_next_meetings(&query, TimeCode::try_from(time).unwrap(), day, None, 2019);
+            assert_eq!(meetings.len(), 1);
+            let meeting = &meetings[0];
+            let period = &meeting.course.sections[0].periods[0];
+            (
+                meeting.day,
+                period.time.unwrap().start.time(),
+                period.location.clone().unwrap(),
+                meeting.in_progress,
+            )
+        };
+        // Lectures are on Tuesday and Friday at 14:00, labs on Wednesday at 10:00 and tests on
+        // Thursday at 18:00
+        assert_eq!(
+            next(1500, Day::Tuesday),
+            (Day::Tuesday, 1400, "DARRIN 308".to_string(), true)
+        );
+        assert_eq!(
+            next(1550, Day::Tuesday),
+            (Day::Wednesday, 1000, "LALLY 104".to_string(), false)
+        );
+        assert_eq!(
+            next(900, Day::Wednesday),
+            (Day::Wednesday, 1000, "LALLY 104".to_string(), false)
+        );
+        assert_eq!(
+            next(1600, Day::Friday),
+            (Day::Tuesday, 1400, "DARRIN 308".to_string(), false)
+        );
+        assert_eq!(
+            next(1400, Day::Friday),
+            (Day::Friday, 1400, "DARRIN 308".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn next_meetings_follow_the_date_of_each_day() {
+        let mut db = schema::bundled_db("201909.json").0;
+        let first_class = models::CalendarDate::new(9, 4);
+        for course in &mut db.courses {
+            for section in course
+                .sections
+                .iter_mut()
+                .filter(|section| section.crn == 81047)
+            {
+                for period in &mut section.periods {
+                    period.date_start = first_class;
+                }
+            }
+        }
+        // The lecture on Tuesday 09/03 is held before the first class, so the lab comes next
+        let meetings = db.find_next_meetings(
+            &CourseQuery::Crn(81047),
+            TimeCode::try_from(900).unwrap(),
+            Day::Monday,
+            models::CalendarDate::new(9, 2),
+            2019,
+        );
+        assert_eq!(meetings.len(), 1);
+        assert_eq!(meetings[0].day, Day::Wednesday);
+    }
+
+    #[test]
+    fn instructors_are_found_by_any_part_of_their_name() {
+        let db = schema::bundled_db("201909.json").0;
+        let names = |name| {
+            db.find_instructors(name)
+                .into_iter()
+                .map(|schedule| schedule.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("kruger"), ["Kruger"]);
+        assert_eq!(names("aboul"), ["Aboul-Seoud"]);
+        assert_eq!(names("Seoud"), ["Aboul-Seoud"]);
+        assert!(names("nobody by this name").is_empty());
+    }
+
+    #[test]
+    fn instructor_timetables_are_sorted_by_day_and_time() {
+        let db = schema::bundled_db("201909.json").0;
+        let kruger = db.find_instructors("kruger").remove(0);
+        let timetable = kruger
+            .timetable()
+            .into_iter()
+            .map(|(day, course, section, period)| {
+                (
+                    day,
+                    course.num,
+                    section.crn,
+                    period.time.unwrap().start.time(),
+                )
             })
-            .map(|a| a.clone().clone())
             .collect::<Vec<_>>();
-        valid.sort();
-        valid
+        // BMED 2960 at 10:00, and two sections of ENGR 2600 at 8:00 and 14:00, twice a week
+        assert_eq!(
+            timetable,
+            [
+                (Day::Monday, 2600, 84443, 800),
+                (Day::Monday, 2960, 85313, 1000),
+                (Day::Monday, 2600, 81038, 1400),
+                (Day::Thursday, 2600, 84443, 800),
+                (Day::Thursday, 2960, 85313, 1000),
+                (Day::Thursday, 2600, 81038, 1400),
+            ]
+        );
+
+        let next = |time, day| {
+            kruger
+                .next_meeting(TimeCode::try_from(time).unwrap(), day, None, 2019)
+                .map(|meeting| {
+                    (
+                        meeting.day,
+                        meeting.course.sections[0].crn,
+                        meeting.in_progress,
+                    )
+                })
+        };
+        assert_eq!(next(1030, Day::Monday), Some((Day::Monday, 85313, true)));
+        assert_eq!(next(1150, Day::Monday), Some((Day::Monday, 81038, false)));
+        assert_eq!(next(1600, Day::Thursday), Some((Day::Monday, 84443, false)));
     }
 }
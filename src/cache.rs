@@ -0,0 +1,207 @@
+//! A compact binary copy of a JSON course DB, kept next to it so that repeated queries skip
+//! parsing the JSON. The cache is rebuilt whenever the JSON changes.
+
+use crate::{
+    models::CourseDB,
+    schema::{self, SchemaError},
+};
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+/// Describes the JSON file a cache was built from, and the build of whereisclass that wrote it
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct CacheHeader {
+    /// The version of whereisclass that wrote the cache, as the binary format follows the models
+    version: String,
+
+    /// The current schema version of the build that wrote the cache. The binary format encodes
+    /// fields by position, so a model change that only bumps the schema must not be misread.
+    models_version: u32,
+
+    /// The size of the JSON file, in bytes
+    len: u64,
+
+    /// When the JSON file was last modified, in nanoseconds since the Unix epoch
+    modified: Option<u128>,
+
+    /// A hash of the contents of the JSON file, see [`hash`]
+    hash: u64,
+
+    /// The schema version the JSON file was written with
+    schema_version: u32,
+}
+
+/// Writes a course DB in the compact binary format.
+pub fn to_writer(writer: &mut impl Write, db: &CourseDB) -> Result<(), rmp_serde::encode::Error> {
+    rmp_serde::encode::write(writer, db)
+}
+
+/// Reads a course DB written by [`to_writer`] with the same version of whereisclass.
+pub fn from_reader(reader: &mut impl Read) -> Result<CourseDB, rmp_serde::decode::Error> {
+    rmp_serde::decode::from_read(reader)
+}
+
+/// The cache file of a JSON course DB, e.g. `201909.json.cache` for `201909.json`
+pub fn cache_path(db_file: &Path) -> PathBuf {
+    let mut cache_file = db_file.as_os_str().to_owned();
+    cache_file.push(".cache");
+    PathBuf::from(cache_file)
+}
+
+/// Reads a JSON course DB along with the schema version it was written with, through its cache.
+///
+/// The cache is used as-is if the size and modification time of the JSON file match. Otherwise
+/// the JSON file is hashed, and only parsed if its contents changed too. Problems with the cache
+/// itself are not errors, but fall back to the JSON file.
+pub fn read_db(db_file: &Path) -> Result<(CourseDB, u32), CacheError> {
+    let metadata = fs::metadata(db_file).map_err(CacheError::IO)?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|since_epoch| since_epoch.as_nanos());
+    let cache_file = cache_path(db_file);
+    let cached = match read_cache(&cache_file, metadata.len()) {
+        Some((header, db)) if modified.is_some() && header.modified == modified => {
+            return Ok((db, header.schema_version));
+        }
+        cached => cached,
+    };
+
+    let content = fs::read(db_file).map_err(CacheError::IO)?;
+    let hash = hash(&content);
+    let (db, schema_version) = match cached {
+        Some((header, db)) if header.hash == hash => (db, header.schema_version),
+        _ => schema::from_reader(content.as_slice()).map_err(CacheError::Schema)?,
+    };
+    let header = CacheHeader {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        models_version: schema::SCHEMA_VERSION,
+        len: metadata.len(),
+        modified,
+        hash,
+        schema_version,
+    };
+    // Failing to write the cache only makes the next query slower
+    let _ = write_cache(&cache_file, &header, &db);
+    Ok((db, schema_version))
+}
+
+/// Reads a cache, if it exists and was written by this version of whereisclass from a JSON file
+/// of the given size
+fn read_cache(cache_file: &Path, len: u64) -> Option<(CacheHeader, CourseDB)> {
+    let mut reader = BufReader::new(File::open(cache_file).ok()?);
+    let header: CacheHeader = rmp_serde::decode::from_read(&mut reader).ok()?;
+    if header.version != env!("CARGO_PKG_VERSION")
+        || header.models_version != schema::SCHEMA_VERSION
+        || header.len != len
+    {
+        return None;
+    }
+    let db = from_reader(&mut reader).ok()?;
+    Some((header, db))
+}
+
+/// Writes a cache to a temporary file next to it first, named after this process, so that
+/// concurrent queries never read a partial cache or write over each other's
+fn write_cache(
+    cache_file: &Path,
+    header: &CacheHeader,
+    db: &CourseDB,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut temp_file = cache_file.as_os_str().to_owned();
+    temp_file.push(format!(".{}.tmp", std::process::id()));
+    let written = File::create(&temp_file)
+        .map_err(Box::<dyn std::error::Error>::from)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            rmp_serde::encode::write(&mut writer, header)?;
+            to_writer(&mut writer, db)?;
+            writer.into_inner().map_err(|err| err.into_error())?;
+            fs::rename(&temp_file, cache_file)?;
+            Ok(())
+        });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_file);
+    }
+    written
+}
+
+/// Errors that can occur reading a course DB through its cache
+#[derive(Debug)]
+pub enum CacheError {
+    /// The JSON file could not be read
+    IO(std::io::Error),
+    /// The JSON file does not hold a course DB
+    Schema(SchemaError),
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IO(err) => write!(f, "{}", err),
+            Self::Schema(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+/// Hashes the contents of a JSON file with 64-bit FNV-1a. Unlike the standard library's hasher,
+/// the result never changes between builds.
+fn hash(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn binary_format_round_trips() {
+        let db = schema::bundled_db("201909.json").0;
+        let mut written = Vec::new();
+        to_writer(&mut written, &db).unwrap();
+        assert_eq!(from_reader(&mut written.as_slice()).unwrap(), db);
+    }
+
+    #[test]
+    fn cache_follows_the_json() {
+        let dir = env::temp_dir().join(format!("whereisclass-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let db_file = dir.join("db.json");
+        let mut db = schema::bundled_db("201909.json").0;
+        db.courses.truncate(10);
+        schema::to_writer(File::create(&db_file).unwrap(), &db).unwrap();
+
+        let (read, _) = read_db(&db_file).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(read, db);
+        assert!(cache_path(&db_file).is_file());
+        let (read, _) = read_db(&db_file).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(read, db);
+
+        db.courses.truncate(5);
+        schema::to_writer(File::create(&db_file).unwrap(), &db).unwrap();
+        let (read, _) = read_db(&db_file).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(read, db);
+        // Only the JSON file and its cache are left, without temporary files
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hash_is_fnv_1a() {
+        assert_eq!(hash(b""), 0xcbf29ce484222325);
+        assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,535 @@
+//! An index of when each room is booked, so that room queries don't have to scan every period of
+//! every course.
+
+use crate::{
+    actions::{
+        EmptyRoom, FindCourseInRoomAtTime, FindEmptyRooms, FindFreeSlots, FreeSlot, RoomBooking,
+    },
+    models, TimeCode,
+};
+use std::collections::BTreeMap;
+
+/// The periods held in each room, built once from a [`CourseDB`](models::CourseDB) and then
+/// queried any number of times.
+pub struct RoomIndex<'a> {
+    db: &'a models::CourseDB,
+    rooms: BTreeMap<&'a str, RoomSchedule<'a>>,
+
+    /// The minutes of passing time kept free before and after every booking
+    buffer: u16,
+}
+
+/// The periods held in a single room, by day
+#[derive(Default)]
+pub struct RoomSchedule<'a> {
+    /// The bookings on each day of the week, starting on Monday, sorted by their start time
+    days: [Vec<Booking<'a>>; 7],
+}
+
+/// A period occupying a room, along with where it is in the course DB
+#[derive(Copy, Clone)]
+pub struct Booking<'a> {
+    /// The indices of the course, section and period in the course DB
+    position: (usize, usize, usize),
+    pub course: &'a models::Course,
+    pub period: &'a models::Period,
+    pub time: models::TimeRange,
+}
+
+impl<'a> RoomIndex<'a> {
+    /// Indexes every period of the course DB that has a time and a room
+    pub fn new(db: &'a models::CourseDB) -> Self { Self::with_buffer(db, 0) }
+
+    /// Indexes the course DB like [`new`](RoomIndex::new), but has queries keep `buffer` minutes
+    /// of passing time free before and after every booking.
+    pub fn with_buffer(db: &'a models::CourseDB, buffer: u16) -> Self {
+        let mut rooms = BTreeMap::<&str, RoomSchedule>::new();
+        for (course_idx, course) in db.courses.iter().enumerate() {
+            for (section_idx, section) in course.sections.iter().enumerate() {
+                for (period_idx, period) in section.periods.iter().enumerate() {
+                    let room = match period.location.as_deref() {
+                        Some(room) => rooms.entry(room).or_default(),
+                        None => continue,
+                    };
+                    let time = match period.time {
+                        Some(time) => time,
+                        None => continue,
+                    };
+                    for day in &period.days {
+                        room.days[*day as usize].push(Booking {
+                            position: (course_idx, section_idx, period_idx),
+                            course,
+                            period,
+                            time,
+                        });
+                    }
+                }
+            }
+        }
+        for schedule in rooms.values_mut() {
+            for bookings in &mut schedule.days {
+                // The sort is stable, so bookings starting together stay in course DB order
+                bookings.sort_by_key(|booking| booking.time.start);
+            }
+        }
+        Self { db, rooms, buffer }
+    }
+
+    /// The schedule of a room, if any period is held in it
+    pub fn room(&self, room: &str) -> Option<&RoomSchedule<'a>> { self.rooms.get(room) }
+
+    /// Every room a period is held in, sorted by name, along with its schedule
+    pub fn rooms(&self) -> impl Iterator<Item = (&'a str, &RoomSchedule<'a>)> {
+        self.rooms.iter().map(|(room, schedule)| (*room, schedule))
+    }
+}
+
+impl<'a> RoomSchedule<'a> {
+    /// The bookings held on a day, sorted by their start time. If a date is given, bookings that
+    /// are not held that week are left out.
+    pub fn bookings_on(
+        &self,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> impl Iterator<Item = &Booking<'a>> {
+        self.days[day as usize]
+            .iter()
+            .filter(move |booking| booking.period.meets_on(day, date))
+    }
+
+    /// The bookings on a day that overlap the given range, keeping `buffer` minutes free around
+    /// each booking. See [`TimeRange::overlaps`](models::TimeRange::overlaps).
+    pub fn overlapping(
+        &self,
+        range: models::TimeRange,
+        buffer: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> impl Iterator<Item = &Booking<'a>> {
+        let bookings = &self.days[day as usize];
+        // Bookings are sorted by start time, so none past this point can overlap
+        let candidates = bookings.partition_point(|booking| {
+            booking.time.start.minutes() <= range.end.minutes().saturating_add(buffer)
+        });
+        bookings[..candidates]
+            .iter()
+            .filter(move |booking| booking.time.overlaps(&range, buffer))
+            .filter(move |booking| booking.period.meets_on(day, date))
+    }
+
+    /// The booking on a day that ends last, with `buffer` minutes to spare, no later than the
+    /// given time. Of bookings ending together, the first in the course DB is picked.
+    pub fn previous(
+        &self,
+        time: TimeCode,
+        buffer: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Option<&Booking<'a>> {
+        self.bookings_on(day, date)
+            .take_while(|booking| booking.time.start < time)
+            .filter(|booking| booking.time.end.minutes().saturating_add(buffer) <= time.minutes())
+            .max_by(|a, b| {
+                a.time
+                    .end
+                    .cmp(&b.time.end)
+                    .then(b.position.cmp(&a.position))
+            })
+    }
+
+    /// The booking on a day that starts first, with `buffer` minutes to spare, no earlier than
+    /// the given time. Of bookings starting together, the first in the course DB is picked.
+    pub fn next(
+        &self,
+        time: TimeCode,
+        buffer: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Option<&Booking<'a>> {
+        let bookings = &self.days[day as usize];
+        let after = bookings.partition_point(|booking| {
+            booking.time.start.minutes() < time.minutes().saturating_add(buffer)
+        });
+        bookings[after..]
+            .iter()
+            .find(|booking| booking.period.meets_on(day, date))
+    }
+
+    /// The intervals between `earliest` and `latest` on a day not taken by any booking, keeping
+    /// `buffer` minutes free around each booking, in order.
+    pub fn free(
+        &self,
+        earliest: TimeCode,
+        latest: TimeCode,
+        buffer: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<models::TimeRange> {
+        let mut free = Vec::new();
+        // Work in minutes, as the buffer may reach past the valid range of times. Busy times are
+        // clamped to the query window, so that they can always be turned back into times.
+        let buffer = i32::from(buffer);
+        let earliest_minutes = i32::from(earliest.minutes());
+        let latest_minutes = i32::from(latest.minutes());
+        let mut since = earliest;
+        for booking in self.bookings_on(day, date) {
+            let busy_start = (i32::from(booking.time.start.minutes()) - buffer)
+                .clamp(earliest_minutes, latest_minutes);
+            let busy_end = (i32::from(booking.time.end.minutes()) + buffer)
+                .clamp(earliest_minutes, latest_minutes);
+            if busy_start >= latest_minutes {
+                break;
+            }
+            if busy_start > i32::from(since.minutes()) {
+                free.push(models::TimeRange {
+                    start: since,
+                    end: minutes_to_time(busy_start),
+                });
+            }
+            if busy_end >= latest_minutes {
+                return free;
+            }
+            since = since.max(minutes_to_time(busy_end));
+        }
+        free.push(models::TimeRange {
+            start: since,
+            end: latest,
+        });
+        free
+    }
+}
+
+/// The time a number of minutes after midnight, which must be within the query window
+fn minutes_to_time(minutes: i32) -> TimeCode {
+    u16::try_from(minutes)
+        .ok()
+        .and_then(|minutes| TimeCode::from_minutes(minutes).ok())
+        .expect("Free interval outside of the valid range of times")
+}
+
+impl<'a> From<&Booking<'a>> for RoomBooking {
+    fn from(booking: &Booking<'a>) -> Self {
+        RoomBooking {
+            dept: booking.course.dept.clone(),
+            num: booking.course.num,
+            time: booking.time,
+            period: booking.period.clone(),
+        }
+    }
+}
+
+impl FindCourseInRoomAtTime for RoomIndex<'_> {
+    fn find_course_in_room_at_time_range(
+        &self,
+        room: &str,
+        time_start: TimeCode,
+        time_end: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<models::Course> {
+        let range = models::TimeRange {
+            start: time_start,
+            end: time_end,
+        };
+        let mut positions = self
+            .room(room)
+            .map(|schedule| {
+                schedule
+                    .overlapping(range, self.buffer, day, date)
+                    .map(|booking| booking.position)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        // Keep the order of the course DB
+        positions.sort_unstable();
+        positions.dedup();
+        let mut clash = Vec::<models::Course>::new();
+        let mut last = None;
+        for (course_idx, section_idx, period_idx) in positions {
+            let course = &self.db.courses[course_idx];
+            let section = &course.sections[section_idx];
+            if last.is_none_or(|(last_course, _)| last_course != course_idx) {
+                clash.push(models::Course {
+                    sections: vec![],
+                    ..course.clone()
+                });
+            }
+            let sections = &mut clash.last_mut().unwrap().sections;
+            if last != Some((course_idx, section_idx)) {
+                sections.push(models::Section {
+                    periods: vec![],
+                    ..section.clone()
+                });
+            }
+            let periods = &mut sections.last_mut().unwrap().periods;
+            periods.push(section.periods[period_idx].clone());
+            last = Some((course_idx, section_idx));
+        }
+        clash
+    }
+}
+
+impl FindEmptyRooms for RoomIndex<'_> {
+    fn find_empty_rooms(
+        &self,
+        time_start: TimeCode,
+        time_end: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<String> {
+        let range = models::TimeRange {
+            start: time_start,
+            end: time_end,
+        };
+        self.rooms()
+            .filter(|(_, schedule)| {
+                schedule
+                    .overlapping(range, self.buffer, day, date)
+                    .next()
+                    .is_none()
+            })
+            .map(|(room, _)| room.to_string())
+            .collect()
+    }
+
+    fn find_empty_rooms_with_neighbours(
+        &self,
+        time_start: TimeCode,
+        time_end: TimeCode,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<EmptyRoom> {
+        self.find_empty_rooms(time_start, time_end, day, date)
+            .into_iter()
+            .map(|room| {
+                let schedule = &self.rooms[room.as_str()];
+                EmptyRoom {
+                    previous: schedule
+                        .previous(time_start, self.buffer, day, date)
+                        .map(RoomBooking::from),
+                    next: schedule
+                        .next(time_end, self.buffer, day, date)
+                        .map(RoomBooking::from),
+                    room,
+                }
+            })
+            .collect()
+    }
+}
+
+impl FindFreeSlots for RoomIndex<'_> {
+    fn find_free_slots(
+        &self,
+        earliest: TimeCode,
+        latest: TimeCode,
+        min_length: u16,
+        day: models::Day,
+        date: Option<models::CalendarDate>,
+    ) -> Vec<FreeSlot> {
+        self.rooms()
+            .flat_map(|(room, schedule)| {
+                schedule
+                    .free(earliest, latest, self.buffer, day, date)
+                    .into_iter()
+                    .filter(|time| time.minutes() >= min_length.max(1))
+                    .map(|time| FreeSlot {
+                        room: room.to_string(),
+                        time,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Day, schema};
+
+    /// Whether a period occupies a room during a range, by checking it directly
+    fn occupies(
+        period: &models::Period,
+        room: &str,
+        time_start: TimeCode,
+        time_end: TimeCode,
+        day: Day,
+    ) -> bool {
+        period.location.as_deref() == Some(room)
+            && period.meets_on(day, None)
+            && period.time.is_some_and(|time| {
+                time.overlaps(
+                    &models::TimeRange {
+                        start: time_start,
+                        end: time_end,
+                    },
+                    0,
+                )
+            })
+    }
+
+    #[test]
+    fn index_matches_a_full_scan() {
+        let db = schema::bundled_db("201909.json").0;
+        let index = RoomIndex::new(&db);
+        let times = [800, 950, 1000, 1150, 1200, 1400, 1550, 1800]
+            .map(|time| TimeCode::try_from(time).unwrap());
+        for day in [Day::Monday, Day::Thursday, Day::Saturday] {
+            for window in times.windows(2) {
+                let (time_start, time_end) = (window[0], window[1]);
+                let empty = index.find_empty_rooms(time_start, time_end, day, None);
+                for (room, _) in index.rooms() {
+                    let occupied = db
+                        .courses
+                        .iter()
+                        .flat_map(|course| course.sections.iter())
+                        .flat_map(|section| section.periods.iter())
+                        .any(|period| occupies(period, room, time_start, time_end, day));
+                    assert_eq!(
+                        !occupied,
+                        empty.iter().any(|empty| empty == room),
+                        "{} on {} from {} to {}",
+                        room,
+                        day,
+                        time_start,
+                        time_end
+                    );
+                    let courses = index
+                        .find_course_in_room_at_time_range(room, time_start, time_end, day, None);
+                    assert_eq!(occupied, !courses.is_empty());
+                    assert!(courses
+                        .iter()
+                        .flat_map(|course| course.sections.iter())
+                        .flat_map(|section| section.periods.iter())
+                        .all(|period| occupies(period, room, time_start, time_end, day)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_are_the_closest_bookings() {
+        let db = schema::bundled_db("201909.json").0;
+        let index = RoomIndex::new(&db);
+        let (time_start, time_end) = (
+            TimeCode::try_from(1200).unwrap(),
+            TimeCode::try_from(1300).unwrap(),
+        );
+        for empty in index.find_empty_rooms_with_neighbours(time_start, time_end, Day::Monday, None)
+        {
+            let bookings = index
+                .room(&empty.room)
+                .unwrap()
+                .bookings_on(Day::Monday, None)
+                .collect::<Vec<_>>();
+            let previous = bookings
+                .iter()
+                .map(|booking| booking.time.end)
+                .filter(|end| *end <= time_start)
+                .max();
+            let next = bookings
+                .iter()
+                .map(|booking| booking.time.start)
+                .filter(|start| *start >= time_end)
+                .min();
+            assert_eq!(empty.previous.map(|booking| booking.time.end), previous);
+            assert_eq!(empty.next.map(|booking| booking.time.start), next);
+        }
+    }
+
+    #[test]
+    fn touching_periods_do_not_clash() {
+        let db = schema::bundled_db("201909.json").0;
+        let time = |time| TimeCode::try_from(time).unwrap();
+        // BMED 2960 is held in CARNEG 206 from 10:00 to 11:50 on Monday
+        let index = RoomIndex::new(&db);
+        let room = "CARNEG 206";
+        assert!(!index
+            .find_course_in_room_at_time_range(room, time(1000), time(1150), Day::Monday, None)
+            .is_empty());
+        assert!(index
+            .find_course_in_room_at_time_range(room, time(1150), time(1200), Day::Monday, None)
+            .is_empty());
+        assert!(index
+            .find_course_in_room_at_time(room, time(1150), Day::Monday, None)
+            .is_empty());
+        assert!(index
+            .find_course_in_room_at_time_range(room, time(900), time(1000), Day::Monday, None)
+            .is_empty());
+
+        // With ten minutes of passing time, the room is only free again at 12:00
+        let index = RoomIndex::with_buffer(&db, 10);
+        assert!(!index
+            .find_course_in_room_at_time(room, time(1159), Day::Monday, None)
+            .is_empty());
+        assert!(index
+            .find_course_in_room_at_time(room, time(1200), Day::Monday, None)
+            .is_empty());
+        let empty =
+            index.find_empty_rooms_with_neighbours(time(1200), time(1210), Day::Monday, None);
+        let empty = empty.iter().find(|empty| empty.room == room).unwrap();
+        assert_eq!(
+            empty.previous.as_ref().map(|booking| booking.time.end),
+            Some(time(1150))
+        );
+    }
+
+    #[test]
+    fn huge_buffers_never_overflow() {
+        let db = schema::bundled_db("201909.json").0;
+        let time = |time| TimeCode::try_from(time).unwrap();
+        let index = RoomIndex::with_buffer(&db, u16::MAX);
+        let room = index.room("CARNEG 206").unwrap();
+        assert!(room
+            .previous(time(1200), u16::MAX, Day::Monday, None)
+            .is_none());
+        assert!(room.next(time(1200), u16::MAX, Day::Monday, None).is_none());
+        let empty =
+            index.find_empty_rooms_with_neighbours(time(1200), time(1300), Day::Monday, None);
+        assert!(empty
+            .iter()
+            .all(|empty| empty.previous.is_none() && empty.next.is_none()));
+        assert!(index
+            .find_free_slots(time(800), time(1800), 10, Day::Monday, None)
+            .iter()
+            .all(|slot| slot.room != "CARNEG 206"));
+    }
+
+    #[test]
+    fn free_slots_fill_the_gaps_between_bookings() {
+        let db = schema::bundled_db("201909.json").0;
+        let time = |time| TimeCode::try_from(time).unwrap();
+        let slots = |index: &RoomIndex, min_length| {
+            index
+                .find_free_slots(time(800), time(1800), min_length, Day::Monday, None)
+                .into_iter()
+                .filter(|slot| slot.room == "CARNEG 206")
+                .map(|slot| (slot.time.start.time(), slot.time.end.time()))
+                .collect::<Vec<_>>()
+        };
+        // CARNEG 206 is booked from 10:00 to 11:50, 14:00 to 15:50 and 18:00 to 19:50 on Monday
+        let index = RoomIndex::new(&db);
+        assert_eq!(
+            slots(&index, 60),
+            vec![(800, 1000), (1150, 1400), (1550, 1800)]
+        );
+        assert_eq!(slots(&index, 121), vec![(1150, 1400), (1550, 1800)]);
+        let index = RoomIndex::with_buffer(&db, 10);
+        assert_eq!(
+            slots(&index, 60),
+            vec![(800, 950), (1200, 1350), (1600, 1750)]
+        );
+
+        for slot in index.find_free_slots(time(700), time(2350), 30, Day::Tuesday, None) {
+            assert!(slot.minutes() >= 30);
+            assert!(index
+                .find_course_in_room_at_time_range(
+                    &slot.room,
+                    slot.time.start,
+                    slot.time.end,
+                    Day::Tuesday,
+                    None
+                )
+                .is_empty());
+        }
+    }
+}
@@ -2,45 +2,104 @@ mod actions;
 mod models;
 mod opt;
 
+#[cfg(feature = "cache")]
+mod cache;
+mod index;
 mod parser;
+mod schema;
 
 use crate::{
+    actions::{
+        CourseFilter, CourseQuery, FilterCourses, FindClass, FindCourseInRoomAtTime,
+        FindCrosslistings, FindEmptyRooms, FindFreeSlots, FindInstructors, FindUnscheduled,
+        Meeting,
+    },
     models::*,
-    opt::{AppWhereIsClass, ParseArgs},
+    opt::{AppWhereIsClass, FreeSlotsArgs, ParseArgs},
     parser::{CourseDBParseError, CourseDBParser},
 };
 use std::{
+    collections::BTreeSet,
     fmt::{Display, Formatter},
     fs::File,
     io::{BufReader, Read},
+    path::Path,
 };
 
 fn main() {
     let args = opt::parse_args();
 
     if let Err(err) = match args {
-        AppWhereIsClass::ParseHtml(args) => parse(args, parser::HtmlParser),
+        AppWhereIsClass::ParseHtml(args) => {
+            let strict = args.strict;
+            parse(args, parser::HtmlParser { strict })
+        }
         #[cfg(feature = "rcosxml")]
-        AppWhereIsClass::ParseRcos(args) => {}
+        AppWhereIsClass::ParseRcos(args) => {
+            let strict = args.strict;
+            parse(args, parser::RcosXmlParser { strict })
+        }
         AppWhereIsClass::FindCourseInRoom {
             db,
             room,
             day,
             time,
-        } => {
-            unimplemented!()
-        }
+            date,
+            buffer,
+        } => find_course_in_room(
+            db.as_path(),
+            room.as_str(),
+            time,
+            day.unwrap_or_else(Day::today),
+            date,
+            buffer,
+        ),
         AppWhereIsClass::EmptyRooms {
             db,
             day,
             time_start,
             time_end,
-        } => {
-            unimplemented!()
-        }
+            date,
+            campus,
+            buffer,
+        } => empty_rooms(
+            db.as_path(),
+            time_start,
+            time_end,
+            day.unwrap_or_else(Day::today),
+            date,
+            campus,
+            buffer,
+        ),
+        AppWhereIsClass::FreeSlots(args) => free_slots(args),
+        AppWhereIsClass::FindCourses { db, filter } => find_courses(db.as_path(), &filter.into()),
+        AppWhereIsClass::WhereIs {
+            db,
+            query,
+            day,
+            time,
+            date,
+        } => where_is(
+            db.as_path(),
+            &CourseQuery::from(query.join(" ").as_str()),
+            day,
+            time,
+            date,
+        ),
+        AppWhereIsClass::Instructor {
+            db,
+            name,
+            day,
+            time,
+            date,
+        } => instructor(db.as_path(), name.join(" ").as_str(), day, time, date),
+        AppWhereIsClass::Crosslisted { db, crn } => crosslisted(db.as_path(), crn),
+        AppWhereIsClass::Unscheduled { db, dept } => unscheduled(db.as_path(), dept.as_deref()),
+        AppWhereIsClass::Migrate { db } => migrate(db.as_path()),
     } {
         eprintln!("An error occurred.");
         eprintln!("{}", err);
+        std::process::exit(1);
     }
 }
 
@@ -48,8 +107,13 @@ enum ApplicationError {
     IOError(std::io::Error),
     JsonSerializationError(serde_json::Error),
     ParseError(CourseDBParseError),
+    DatabaseError(schema::SchemaError),
+    InvalidTime(TimeCodeError),
     OutputExists,
     InputDoesNotExist,
+    NoCampusData,
+    ReversedRange(TimeCode, TimeCode),
+    DatabaseDoesNotExist,
 }
 
 impl Display for ApplicationError {
@@ -58,11 +122,26 @@ impl Display for ApplicationError {
             Self::IOError(err) => write!(f, "IO Error: {}", err),
             Self::JsonSerializationError(err) => write!(f, "Error during JSON writing: {}", err),
             Self::ParseError(err) => write!(f, "Error during parsing: {}", err),
+            Self::DatabaseError(err) => write!(f, "Course DB is corrupt or invalid: {}", err),
+            Self::InvalidTime(err) => write!(f, "Cannot look up the current time: {}", err),
             Self::OutputExists => write!(
                 f,
                 "Refusing to overwrite existing output file. Use --force to override."
             ),
             Self::InputDoesNotExist => write!(f, "Input file does not exist."),
+            Self::NoCampusData => write!(
+                f,
+                "Course DB has no campus data to filter by. Parse it from an SIS HTML table to \
+                 use --campus."
+            ),
+            Self::ReversedRange(start, end) => {
+                write!(
+                    f,
+                    "The range ends at {} before it starts at {}.",
+                    end, start
+                )
+            }
+            Self::DatabaseDoesNotExist => write!(f, "Course DB file does not exist."),
         }
     }
 }
@@ -70,26 +149,38 @@ impl Display for ApplicationError {
 fn parse(parse_args: ParseArgs, parser: impl CourseDBParser) -> Result<(), ApplicationError> {
     if parse_args.file.exists() {
         if parse_args.force || !parse_args.output.exists() {
-            let mut f = File::open(parse_args.file);
-            f.map(|file| BufReader::new(file))
+            let f = File::open(parse_args.file);
+            f.map(BufReader::new)
                 .and_then(|mut reader| {
                     let mut content = String::with_capacity(32000);
                     reader.read_to_string(&mut content).map(|_| content)
                 })
-                .map_err(|err| ApplicationError::IOError(err))
+                .map_err(ApplicationError::IOError)
                 .and_then(|content| {
                     parser
                         .parse(content.as_str())
-                        .map_err(|err| ApplicationError::ParseError(err))
+                        .map_err(ApplicationError::ParseError)
+                })
+                .map(|parsed| {
+                    for warning in &parsed.warnings {
+                        eprintln!("Warning: {}", warning);
+                    }
+                    println!(
+                        "Read {} courses with {} warning{}",
+                        parsed.db.courses.len(),
+                        parsed.warnings.len(),
+                        if parsed.warnings.len() != 1 { "s" } else { "" }
+                    );
+                    println!("Course DB: {}", parsed.db.semester);
+                    parsed.db
                 })
                 .and_then(|db| {
                     File::create(parse_args.output.as_path())
-                        .map_err(|err| ApplicationError::IOError(err))
+                        .map_err(ApplicationError::IOError)
                         .map(|file| (db, file))
                 })
                 .and_then(|(db, file)| {
-                    serde_json::to_writer(file, &db)
-                        .map_err(|err| ApplicationError::JsonSerializationError(err))
+                    schema::to_writer(file, &db).map_err(ApplicationError::JsonSerializationError)
                 })
         } else {
             Err(ApplicationError::OutputExists)
@@ -99,29 +190,558 @@ fn parse(parse_args: ParseArgs, parser: impl CourseDBParser) -> Result<(), Appli
     }
 }
 
-// fn load_db(db_file: &str) -> CourseDB {
-//     let f = File::open(db_file).expect("Opening file");
-//     serde_json::from_reader(f).expect("Parsing CourseDB")
-// }
-//
-// fn empty_rooms(db_file: &str, time_start: &str, time_end: &str, day: &str) {
-//     let db = load_db(db_file);
-//     let empty = db.find_empty_rooms(time_start.parse().unwrap(), time_end.parse().unwrap(), Day::from(day));
-//     println!("{} empty room{} found between {} and {}:\n", empty.len(), if empty.len() != 1 { "s" } else {""}, time_start, time_end );
-//     for room in empty {
-//         println!("{}", room);
-//     }
-// }
-//
-// fn find_course_in_room(db_file: &str, room: &str, time: &str, day: &str) {
-//     println!("{} -- ", room);
-//     let courses = load_db(db_file).find_course_in_room_at_time(room, time.parse().unwrap(), Day::from(day));
-//     println!("Found the following course{}:", if courses.len() != 1 { "s" } else {""});
-//     for course in courses {
-//         println!("{} {} -- {}", course.dept, course.num, course.name);
-//     }
-// }
-//
+/// Reads a course DB written with any schema version, along with the version it was written with
+#[cfg(feature = "cache")]
+fn read_db(db_file: &Path) -> Result<(CourseDB, u32), ApplicationError> {
+    if db_file.is_file() {
+        cache::read_db(db_file).map_err(|err| match err {
+            cache::CacheError::IO(err) => ApplicationError::IOError(err),
+            cache::CacheError::Schema(err) => ApplicationError::DatabaseError(err),
+        })
+    } else {
+        Err(ApplicationError::DatabaseDoesNotExist)
+    }
+}
+
+/// Reads a course DB written with any schema version, along with the version it was written with
+#[cfg(not(feature = "cache"))]
+fn read_db(db_file: &Path) -> Result<(CourseDB, u32), ApplicationError> {
+    if db_file.is_file() {
+        File::open(db_file)
+            .map(BufReader::new)
+            .map_err(ApplicationError::IOError)
+            .and_then(|reader| schema::from_reader(reader).map_err(ApplicationError::DatabaseError))
+    } else {
+        Err(ApplicationError::DatabaseDoesNotExist)
+    }
+}
+
+/// Loads a course DB, and says which term it describes so that results are never mistaken for
+/// another term's
+/// The year dates given for a course DB fall in: the year its term starts in, or else this year
+fn term_year(db: &CourseDB) -> i32 {
+    db.semester
+        .year()
+        .unwrap_or_else(|| chrono::Datelike::year(&chrono::Local::now()))
+}
+
+fn load_db(db_file: &Path) -> Result<CourseDB, ApplicationError> {
+    let (db, version) = read_db(db_file)?;
+    if version < schema::SCHEMA_VERSION {
+        eprintln!(
+            "Note: {} uses the old schema version {}. Run `whereisclass migrate` to upgrade it.",
+            db_file.display(),
+            version
+        );
+    }
+    println!("Course DB: {}\n", db.semester);
+    Ok(db)
+}
+
+/// Rewrites a course DB with the current schema version
+fn migrate(db_file: &Path) -> Result<(), ApplicationError> {
+    let (db, version) = read_db(db_file)?;
+    if version == schema::SCHEMA_VERSION {
+        println!(
+            "{} is already at schema version {}",
+            db_file.display(),
+            version
+        );
+        return Ok(());
+    }
+    // Write next to the DB first, so that a failed write never loses the original
+    let mut temp_file = db_file.as_os_str().to_owned();
+    temp_file.push(".tmp");
+    File::create(&temp_file)
+        .map_err(ApplicationError::IOError)
+        .and_then(|file| {
+            schema::to_writer(file, &db).map_err(ApplicationError::JsonSerializationError)
+        })
+        .and_then(|_| std::fs::rename(&temp_file, db_file).map_err(ApplicationError::IOError))?;
+    println!(
+        "Migrated {} from schema version {} to {}",
+        db_file.display(),
+        version,
+        schema::SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+fn find_course_in_room(
+    db_file: &Path,
+    room: &str,
+    time: TimeCode,
+    day: Day,
+    date: Option<CalendarDate>,
+    buffer: u16,
+) -> Result<(), ApplicationError> {
+    let db = load_db(db_file)?;
+    let courses = index::RoomIndex::with_buffer(&db, buffer)
+        .find_course_in_room_at_time(room, time, day, date);
+    let crns = courses
+        .iter()
+        .flat_map(|course| course.sections.iter().map(|section| section.crn))
+        .collect::<Vec<_>>();
+    match date {
+        Some(date) => println!("{} -- {} {} at {}", room, day, date, time),
+        None => println!("{} -- {} at {}", room, day, time),
+    }
+    println!(
+        "Found {} course{}:",
+        courses.len(),
+        if courses.len() != 1 { "s" } else { "" }
+    );
+    for course in courses {
+        println!("{} {} -- {}", course.dept, course.num, course.name);
+        for section in &course.sections {
+            match section.seats {
+                Some(seats) => println!(
+                    "    Section {} (CRN {}), {}",
+                    section.num, section.crn, seats
+                ),
+                None => println!("    Section {} (CRN {})", section.num, section.crn),
+            }
+            // Crosslisted sections are one class, so meeting together is not a conflict
+            let together = db
+                .find_crosslisted_siblings(section.crn)
+                .into_iter()
+                .filter(|sibling| crns.contains(sibling))
+                .map(|sibling| format!("CRN {}", sibling))
+                .collect::<Vec<_>>();
+            if !together.is_empty() {
+                println!("        Crosslisted with {}", together.join(", "));
+            }
+            for period in &section.periods {
+                println!(
+                    "        {} {}, {}",
+                    describe_time(period),
+                    period
+                        .period_type
+                        .as_ref()
+                        .map(|period_type| period_type.to_string())
+                        .unwrap_or_else(|| "Period".to_string()),
+                    describe_instructors(period)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lists the days of a period, or "TBA" if they are not known yet
+fn describe_days(period: &Period) -> String {
+    if period.days.is_empty() {
+        "TBA".to_string()
+    } else {
+        period
+            .days
+            .iter()
+            .map(|day| day.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Shows the time of a period, or "TBA" if it is not known yet
+fn describe_time(period: &Period) -> String {
+    period
+        .time
+        .map(|time| time.to_string())
+        .unwrap_or_else(|| "TBA".to_string())
+}
+
+/// Lists the instructors of a period, or "Staff" if none are known yet
+fn describe_instructors(period: &Period) -> String {
+    if period.instructors.is_empty() {
+        "Staff".to_string()
+    } else {
+        period
+            .instructors
+            .iter()
+            .map(|instructor| instructor.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn find_courses(db_file: &Path, filter: &CourseFilter) -> Result<(), ApplicationError> {
+    let db = load_db(db_file)?;
+    let courses = db.filter_courses(filter);
+    println!(
+        "Found {} course{}:",
+        courses.len(),
+        if courses.len() != 1 { "s" } else { "" }
+    );
+    for course in courses {
+        let credits = course
+            .credits
+            .map(|credits| format!("{} credits", credits))
+            .unwrap_or_else(|| "unknown credits".to_string());
+        let grade_type = course
+            .grade_type
+            .as_ref()
+            .map(|grade_type| grade_type.to_string())
+            .unwrap_or_else(|| "unknown grading".to_string());
+        println!(
+            "{} {} -- {} ({}, {})",
+            course.dept, course.num, course.name, credits, grade_type
+        );
+        for section in &course.sections {
+            let campus = section
+                .campus
+                .as_ref()
+                .map(|campus| format!(", {}", campus))
+                .unwrap_or_default();
+            println!(
+                "    Section {} (CRN {}){}",
+                section.num, section.crn, campus
+            );
+            if !section.attributes.is_empty() {
+                println!("        {}", section.attributes.join(", "));
+            }
+            for period in &section.periods {
+                println!(
+                    "        {} {}, {}",
+                    describe_days(period),
+                    describe_time(period),
+                    period.location.as_deref().unwrap_or("no room")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn where_is(
+    db_file: &Path,
+    query: &CourseQuery,
+    day: Option<Day>,
+    time: Option<TimeCode>,
+    date: Option<CalendarDate>,
+) -> Result<(), ApplicationError> {
+    let db = load_db(db_file)?;
+    if day.is_none() && time.is_none() {
+        let courses = db.find_class(query);
+        println!(
+            "Found {} course{}:",
+            courses.len(),
+            if courses.len() != 1 { "s" } else { "" }
+        );
+        for course in courses {
+            println!("{} {} -- {}", course.dept, course.num, course.name);
+            for section in &course.sections {
+                println!("    Section {} (CRN {})", section.num, section.crn);
+                for period in &section.periods {
+                    println!(
+                        "        {} {}, {}",
+                        describe_days(period),
+                        describe_time(period),
+                        period.location.as_deref().unwrap_or("no room")
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let day = day.unwrap_or_else(Day::today);
+    let time = match time {
+        Some(time) => time,
+        None => TimeCode::now().map_err(ApplicationError::InvalidTime)?,
+    };
+    let meetings = db.find_next_meetings(query, time, day, date, term_year(&db));
+    match date {
+        Some(date) => println!("{} {} at {}", day, date, time),
+        None => println!("{} at {}", day, time),
+    }
+    println!(
+        "Found {} section{} meeting now or next:",
+        meetings.len(),
+        if meetings.len() != 1 { "s" } else { "" }
+    );
+    for meeting in meetings {
+        let course = &meeting.course;
+        let section = &course.sections[0];
+        println!(
+            "{} {} -- {}, section {} (CRN {})",
+            course.dept, course.num, course.name, section.num, section.crn
+        );
+        describe_meeting(&meeting);
+    }
+    Ok(())
+}
+
+/// Prints where a meeting is held, and until or from when
+fn describe_meeting(meeting: &Meeting) {
+    let period = &meeting.course.sections[0].periods[0];
+    let room = period.location.as_deref().unwrap_or("no room");
+    match period.time {
+        Some(time) if meeting.in_progress => println!(
+            "    Now in {} until {}, {}",
+            room,
+            time.end,
+            describe_instructors(period)
+        ),
+        _ => println!(
+            "    Next in {} on {} {}, {}",
+            room,
+            meeting.day,
+            describe_time(period),
+            describe_instructors(period)
+        ),
+    }
+}
+
+fn instructor(
+    db_file: &Path,
+    name: &str,
+    day: Option<Day>,
+    time: Option<TimeCode>,
+    date: Option<CalendarDate>,
+) -> Result<(), ApplicationError> {
+    let db = load_db(db_file)?;
+    let schedules = db.find_instructors(name);
+    println!(
+        "Found {} instructor{} matching \"{}\":",
+        schedules.len(),
+        if schedules.len() != 1 { "s" } else { "" },
+        name
+    );
+    if day.is_none() && time.is_none() {
+        for schedule in schedules {
+            println!("{}", schedule.name);
+            for (day, course, section, period) in schedule.timetable() {
+                println!(
+                    "    {:<9} {} {} {} section {} (CRN {}), {}",
+                    day.to_string(),
+                    describe_time(period),
+                    course.dept,
+                    course.num,
+                    section.num,
+                    section.crn,
+                    period.location.as_deref().unwrap_or("no room")
+                );
+            }
+            for course in &schedule.courses {
+                for section in &course.sections {
+                    for period in section
+                        .periods
+                        .iter()
+                        .filter(|period| period.time.is_none() || period.days.is_empty())
+                    {
+                        println!(
+                            "    {:<9} {} {} {} section {} (CRN {}), {}",
+                            describe_days(period),
+                            describe_time(period),
+                            course.dept,
+                            course.num,
+                            section.num,
+                            section.crn,
+                            period.location.as_deref().unwrap_or("no room")
+                        );
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let day = day.unwrap_or_else(Day::today);
+    let time = match time {
+        Some(time) => time,
+        None => TimeCode::now().map_err(ApplicationError::InvalidTime)?,
+    };
+    let year = term_year(&db);
+    for schedule in schedules {
+        println!("{}", schedule.name);
+        match schedule.next_meeting(time, day, date, year) {
+            Some(meeting) => {
+                let course = &meeting.course;
+                let section = &course.sections[0];
+                println!(
+                    "    {} {} -- {}, section {} (CRN {})",
+                    course.dept, course.num, course.name, section.num, section.crn
+                );
+                describe_meeting(&meeting);
+            }
+            None => println!("    Not teaching during the week"),
+        }
+    }
+    Ok(())
+}
+
+fn unscheduled(db_file: &Path, dept: Option<&str>) -> Result<(), ApplicationError> {
+    let courses = load_db(db_file)?
+        .find_unscheduled()
+        .into_iter()
+        .filter(|course| dept.is_none_or(|dept| course.dept.eq_ignore_ascii_case(dept)))
+        .collect::<Vec<_>>();
+    let sections = courses
+        .iter()
+        .map(|course| course.sections.len())
+        .sum::<usize>();
+    println!(
+        "Found {} unscheduled section{}:",
+        sections,
+        if sections != 1 { "s" } else { "" }
+    );
+    for course in &courses {
+        println!("{} {} -- {}", course.dept, course.num, course.name);
+        for section in &course.sections {
+            println!("    Section {} (CRN {})", section.num, section.crn);
+            if section.periods.is_empty() {
+                println!("        No meetings listed");
+            }
+            for period in &section.periods {
+                println!(
+                    "        {} {}, {}",
+                    describe_days(period),
+                    describe_time(period),
+                    period.location.as_deref().unwrap_or("no room")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn crosslisted(db_file: &Path, crn: u32) -> Result<(), ApplicationError> {
+    let db = load_db(db_file)?;
+    let describe = |crn: u32| {
+        db.courses
+            .iter()
+            .find_map(|course| {
+                course
+                    .sections
+                    .iter()
+                    .find(|section| section.crn == crn)
+                    .map(|section| {
+                        format!(
+                            "CRN {} -- {} {} section {} -- {}",
+                            crn, course.dept, course.num, section.num, course.name
+                        )
+                    })
+            })
+            .unwrap_or_else(|| format!("CRN {} -- not in the course DB", crn))
+    };
+    match db.find_crosslisting(crn) {
+        Some(crosslisting) => {
+            println!("{}", describe(crn));
+            println!(
+                "Shares {} seat{} with:",
+                crosslisting.seats,
+                if crosslisting.seats != 1 { "s" } else { "" }
+            );
+            for sibling in db.find_crosslisted_siblings(crn) {
+                println!("    {}", describe(sibling));
+            }
+        }
+        None => println!("{} is not crosslisted.", describe(crn)),
+    }
+    Ok(())
+}
+
+/// The rooms used by sections on a campus, to limit a query to, if a campus is given. Rooms are
+/// booked by sections on every campus, so only the rooms listed are restricted.
+fn campus_rooms<'a>(
+    db: &'a CourseDB,
+    campus: Option<&Campus>,
+) -> Result<Option<BTreeSet<&'a str>>, ApplicationError> {
+    campus
+        .map(|campus| {
+            db.rooms_on_campus(campus)
+                .ok_or(ApplicationError::NoCampusData)
+        })
+        .transpose()
+}
+
+fn empty_rooms(
+    db_file: &Path,
+    time_start: TimeCode,
+    time_end: TimeCode,
+    day: Day,
+    date: Option<CalendarDate>,
+    campus: Option<Campus>,
+    buffer: u16,
+) -> Result<(), ApplicationError> {
+    if time_end < time_start {
+        return Err(ApplicationError::ReversedRange(time_start, time_end));
+    }
+    let db = load_db(db_file)?;
+    let rooms = campus_rooms(&db, campus.as_ref())?;
+    let mut empty = index::RoomIndex::with_buffer(&db, buffer)
+        .find_empty_rooms_with_neighbours(time_start, time_end, day, date);
+    if let Some(rooms) = rooms {
+        empty.retain(|room| rooms.contains(room.room.as_str()));
+    }
+    println!(
+        "{} empty room{} found on {}{} between {} and {}:\n",
+        empty.len(),
+        if empty.len() != 1 { "s" } else { "" },
+        day,
+        date.map(|date| format!(" {}", date)).unwrap_or_default(),
+        time_start,
+        time_end
+    );
+    for room in empty {
+        let since = room
+            .previous
+            .map(|prev| format!("{} (after {} {})", prev.time.end, prev.dept, prev.num))
+            .unwrap_or_else(|| "start of day".to_string());
+        let until = room
+            .next
+            .map(|next| format!("{} (before {} {})", next.time.start, next.dept, next.num))
+            .unwrap_or_else(|| "end of day".to_string());
+        println!("{} -- free from {} until {}", room.room, since, until);
+    }
+    Ok(())
+}
+
+fn free_slots(args: FreeSlotsArgs) -> Result<(), ApplicationError> {
+    if args.latest < args.earliest {
+        return Err(ApplicationError::ReversedRange(args.earliest, args.latest));
+    }
+    let db = load_db(args.db.as_path())?;
+    let rooms = campus_rooms(&db, args.campus.as_ref())?;
+    let day = args.day.unwrap_or_else(Day::today);
+    let mut slots = index::RoomIndex::with_buffer(&db, args.buffer).find_free_slots(
+        args.earliest,
+        args.latest,
+        args.min_length,
+        day,
+        args.date,
+    );
+    if let Some(rooms) = rooms {
+        slots.retain(|slot| rooms.contains(slot.room.as_str()));
+    }
+    if args.longest_first {
+        // Slots of the same length stay sorted by room
+        slots.sort_by_key(|slot| std::cmp::Reverse(slot.minutes()));
+    }
+    println!(
+        "{} free slot{} of at least {} minutes found on {}{} between {} and {}:\n",
+        slots.len(),
+        if slots.len() != 1 { "s" } else { "" },
+        args.min_length,
+        day,
+        args.date
+            .map(|date| format!(" {}", date))
+            .unwrap_or_default(),
+        args.earliest,
+        args.latest
+    );
+    for slot in slots {
+        println!(
+            "{} -- free from {} until {} ({} minutes)",
+            slot.room,
+            slot.time.start,
+            slot.time.end,
+            slot.minutes()
+        );
+    }
+    Ok(())
+}
+
 // fn parsehtml(input: PathBuf, output: PathBuf) {
 //     std_parse(input, output, |s| htmlparser::parse_html(s))
 // }
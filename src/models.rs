@@ -1,3 +1,4 @@
+use chrono::{Datelike, Timelike};
 use serde_derive::{Deserialize, Serialize};
 use std::{
     fmt::{Debug, Display, Formatter},
@@ -9,10 +10,97 @@ use std::{
 const VALID_TIME_RANGE: RangeInclusive<u16> = 700..=2350;
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
-/// A (de)serializable database of courses. Literally just wraps a Vec<Course>
+/// A (de)serializable database of courses, along with how their sections are crosslisted
 pub struct CourseDB {
+    /// Which term the database describes, and where it was read from
+    #[serde(default)]
+    pub semester: Semester,
+
     /// The courses in the database
     pub courses: Vec<Course>,
+
+    /// Groups of sections that are crosslisted with each other
+    #[serde(default)]
+    pub crosslistings: Vec<Crosslisting>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// (De)serializable information about the term a [`CourseDB`] describes. Every field is optional,
+/// as not every source lists all of them.
+pub struct Semester {
+    /// The registrar's term code, the year and month the term starts in `yyyymm` (e.g. 202001)
+    pub term: Option<u32>,
+
+    /// The human-readable name of the term (e.g. "Spring 2020")
+    pub description: Option<String>,
+
+    /// The format the database was parsed from
+    pub source: Option<SourceFormat>,
+
+    /// When the source was generated, in seconds since the Unix epoch
+    pub timestamp: Option<u64>,
+}
+
+impl Semester {
+    /// The year the term starts in, read from its term code
+    pub fn year(&self) -> Option<i32> { self.term.and_then(|term| i32::try_from(term / 100).ok()) }
+}
+
+impl Display for Semester {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.description, self.term) {
+            (Some(description), Some(term)) => write!(f, "{} (term {})", description, term)?,
+            (Some(description), None) => write!(f, "{}", description)?,
+            (None, Some(term)) => write!(f, "Term {}", term)?,
+            (None, None) => write!(f, "Unknown term")?,
+        }
+        if let Some(source) = self.source {
+            write!(f, ", read from {}", source)?;
+        }
+        if let Some(timestamp) = self.timestamp {
+            write!(f, ", generated {}", format_timestamp(timestamp))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time, like `2019-10-08 09:46 UTC`
+fn format_timestamp(timestamp: u64) -> String {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| format!("{} seconds after the Unix epoch", timestamp))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable enum of the formats a [`CourseDB`] can be parsed from
+pub enum SourceFormat {
+    /// A table of classes as listed by SIS
+    SisHtml,
+    /// An RCOS course database XML file
+    RcosXml,
+}
+
+impl Display for SourceFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SisHtml => write!(f, "an SIS HTML table"),
+            Self::RcosXml => write!(f, "an RCOS XML file"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+/// A (de)serializable group of crosslisted sections. Crosslisted sections are the same class
+/// offered under several course numbers, so they meet together and share their seats.
+pub struct Crosslisting {
+    /// The number of seats shared by all sections in the group
+    pub seats: u32,
+
+    /// The CRNs of the sections in the group
+    pub crns: Vec<u32>,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -30,8 +118,94 @@ pub struct Course {
 
     /// The sections this course has.
     pub sections: Vec<Section>,
+
+    /// The number of credit hours the course is worth.
+    ///
+    /// Optional, `Some(...)` if the source lists credit hours, `None` otherwise.
+    #[serde(default)]
+    pub credits: Option<CreditRange>,
+
+    /// How the course is graded.
+    ///
+    /// Optional, `Some(...)` if the source lists the grading mode, `None` otherwise.
+    #[serde(default)]
+    pub grade_type: Option<GradeType>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable inclusive range of credit hours. Most courses are worth a fixed number of
+/// credits, in which case `min == max`.
+pub struct CreditRange {
+    /// The fewest credit hours the course can be taken for
+    pub min: u8,
+
+    /// The most credit hours the course can be taken for
+    pub max: u8,
+}
+
+impl CreditRange {
+    /// Whether the course can be taken for the given number of credit hours
+    pub fn contains(&self, credits: u8) -> bool { self.min <= credits && credits <= self.max }
+}
+
+impl Display for CreditRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.min == self.max {
+            write!(f, "{}", self.min)
+        } else {
+            write!(f, "{}-{}", self.min, self.max)
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable enum that represents how a course is graded
+pub enum GradeType {
+    Letter,
+    SatisfactoryUnsatisfactory,
+    NonGraded,
+    Other(String),
+}
+
+impl Display for GradeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Letter => write!(f, "Letter"),
+            Self::SatisfactoryUnsatisfactory => write!(f, "Satisfactory/Unsatisfactory"),
+            Self::NonGraded => write!(f, "Non-graded"),
+            Self::Other(other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl FromStr for GradeType {
+    type Err = GradeTypeParseError;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        match from.trim().to_ascii_lowercase().as_str() {
+            "letter" => Ok(Self::Letter),
+            "satisfactory/unsatisfactory" | "s/u" | "su" => Ok(Self::SatisfactoryUnsatisfactory),
+            "non-graded" | "nongraded" | "ng" => Ok(Self::NonGraded),
+            _ => Err(GradeTypeParseError(from.into())),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GradeTypeParseError(String);
+
+impl Display for GradeTypeParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Input (\"{}\") must be one of: [letter, s/u, non-graded]",
+            self.0
+        )
+    }
 }
 
+impl std::error::Error for GradeTypeParseError {}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 /// A (de)serializable section structure, containing info pertinent to sections.
 /// This includes information like the CRN, the section number, and the class periods
@@ -40,30 +214,161 @@ pub struct Section {
     /// RPI Course registration number
     pub crn: u32,
 
-    /// The index of the section
-    pub num: u8,
+    /// The identifier of the section, such as "01", "H01" or "L1"
+    pub num: SectionId,
 
     /// Which periods the section meets
     pub periods: Vec<Period>,
 
     /// Notes applicable to this section in `String` format.
     pub notes: Vec<String>,
+
+    /// Seats in the section itself.
+    ///
+    /// Optional, `Some(...)` if the source lists seat counts, `None` otherwise.
+    #[serde(default)]
+    pub seats: Option<SeatCount>,
+
+    /// Seats on the section's waitlist. See [`seats`](Section::seats).
+    #[serde(default)]
+    pub waitlist: Option<SeatCount>,
+
+    /// Seats shared by the section and the sections it is crosslisted with.
+    /// See [`seats`](Section::seats).
+    #[serde(default)]
+    pub crosslist: Option<SeatCount>,
+
+    /// The campus the section is held on.
+    ///
+    /// Optional, `Some(...)` if the source lists campuses, `None` otherwise.
+    #[serde(default)]
+    pub campus: Option<Campus>,
+
+    /// The attributes of the section, such as "Communication Intensive" or "HASS Inquiry".
+    #[serde(default)]
+    pub attributes: Vec<String>,
+}
+
+impl Section {
+    /// Whether the section has an attribute, ignoring case. Attributes may be abbreviated by
+    /// their initials, so "CI" matches "Communication Intensive".
+    pub fn has_attribute(&self, attribute: &str) -> bool {
+        self.attributes.iter().any(|candidate| {
+            let initials = candidate
+                .split_whitespace()
+                .filter_map(|word| word.chars().next())
+                .collect::<String>();
+            candidate.eq_ignore_ascii_case(attribute) || initials.eq_ignore_ascii_case(attribute)
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+/// A (de)serializable section identifier, as listed by the source (e.g. "01", "H01", "L1").
+///
+/// Identifiers are ordered by their letter prefix and then by their number, so "2" comes before
+/// "10" and "H01" comes after "02".
+pub struct SectionId(String);
+
+impl SectionId {
+    /// The identifier split into its letter prefix, its number (if any) and whatever follows
+    fn parts(&self) -> (&str, Option<u32>, &str) {
+        let digits_start = self
+            .0
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(self.0.len());
+        let (prefix, rest) = self.0.split_at(digits_start);
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (digits, suffix) = rest.split_at(digits_end);
+        (prefix, digits.parse().ok(), suffix)
+    }
+}
+
+impl Display for SectionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.0) }
+}
+
+impl<S: AsRef<str>> From<S> for SectionId {
+    fn from(s: S) -> Self { Self(s.as_ref().trim().to_string()) }
+}
+
+impl Ord for SectionId {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.parts()
+            .cmp(&other.parts())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for SectionId {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable enum that represents a campus
+pub enum Campus {
+    Troy,
+    Other(String),
+}
+
+impl Display for Campus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Troy => write!(f, "Troy"),
+            Self::Other(other) => write!(f, "{}", other),
+        }
+    }
+}
+
+impl<S: AsRef<str>> From<S> for Campus {
+    fn from(from: S) -> Self {
+        let from = from.as_ref().trim();
+        if from.eq_ignore_ascii_case("T") || from.eq_ignore_ascii_case("Troy") {
+            Self::Troy
+        } else {
+            Self::Other(from.to_string())
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable count of the seats in a section, waitlist, or crosslisting
+pub struct SeatCount {
+    /// The number of seats available in total
+    pub capacity: u32,
+
+    /// The number of seats taken
+    pub actual: u32,
+
+    /// The number of seats still open. Negative if the seats are overfilled.
+    pub remaining: i32,
+}
+
+impl Display for SeatCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} taken, {} open",
+            self.actual, self.capacity, self.remaining
+        )
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 /// A (de)serializable period structure that defines when a section meets
 pub struct Period {
-    /// The starting time - This should be in 24h military time `hhmm`.
-    /// For example, 1:35 PM --> `1335`
-    pub time_start: TimeCode,
-
-    /// The ending time. See [`time_start`](Period::time_start) for information on format.
-    pub time_end: TimeCode,
+    /// When the period meets during the day.
+    ///
+    /// Optional, `Some(...)` if the time is known, `None` if it is still to be announced (TBA).
+    #[serde(flatten, deserialize_with = "TimeRange::deserialize_optional")]
+    pub time: Option<TimeRange>,
 
-    /// The name of the instructor of this session. For now only holds the primary instructor
-    pub instructor: String,
+    /// The instructors of this session. Empty if the instructors are not yet known ("Staff").
+    pub instructors: Vec<Instructor>,
 
-    /// The list of days this period is held on
+    /// The list of days this period is held on. Empty if the days are still to be announced (TBA).
     pub days: Vec<Day>,
 
     /// The location of this period.
@@ -75,27 +380,286 @@ pub struct Period {
     ///
     /// Optional, `Some(...)` if the period type is known, otherwise `None`.
     pub period_type: Option<PeriodType>,
+
+    /// The first date this period is held on.
+    ///
+    /// Optional, `Some(...)` if the source lists dates, otherwise `None`, in which case the period
+    /// is held all term.
+    #[serde(default)]
+    pub date_start: Option<CalendarDate>,
+
+    /// The last date this period is held on. See [`date_start`](Period::date_start).
+    #[serde(default)]
+    pub date_end: Option<CalendarDate>,
+}
+
+impl Period {
+    /// Whether the period is held on the given day. If a date is given, the period must also be
+    /// held during the weeks the date falls in.
+    pub fn meets_on(&self, day: Day, date: Option<CalendarDate>) -> bool {
+        let in_range = date.is_none_or(|date| {
+            self.date_start.is_none_or(|start| start <= date)
+                && self.date_end.is_none_or(|end| date <= end)
+        });
+        in_range && self.days.contains(&day)
+    }
+
+    /// Whether the period has both a time and days, rather than being to be announced (TBA)
+    pub fn is_scheduled(&self) -> bool { self.time.is_some() && !self.days.is_empty() }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable span of time during a day
+pub struct TimeRange {
+    /// The starting time - This should be in 24h military time `hhmm`.
+    /// For example, 1:35 PM --> `1335`
+    #[serde(rename = "time_start")]
+    pub start: TimeCode,
+
+    /// The ending time. See [`start`](TimeRange::start) for information on format.
+    #[serde(rename = "time_end")]
+    pub end: TimeCode,
+}
+
+impl TimeRange {
+    /// Reads the flattened time of a period, which is TBA only if both times are absent. Unlike
+    /// a plain flattened `Option`, a malformed or half-specified time is an error.
+    fn deserialize_optional<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Self>, D::Error> {
+        #[derive(Deserialize)]
+        struct OptionalTimeRange {
+            time_start: Option<TimeCode>,
+            time_end: Option<TimeCode>,
+        }
+
+        let time = <OptionalTimeRange as serde::Deserialize>::deserialize(deserializer)?;
+        match (time.time_start, time.time_end) {
+            (Some(start), Some(end)) => Ok(Some(Self { start, end })),
+            (None, None) => Ok(None),
+            _ => Err(serde::de::Error::custom(
+                "expected both time_start and time_end, or neither",
+            )),
+        }
+    }
+
+    /// The length of the range, in minutes
+    pub fn minutes(&self) -> u16 { self.end.minutes_since(self.start).max(0) as u16 }
+
+    /// Whether the ranges overlap, keeping `buffer` minutes of passing time free before and after
+    /// this range.
+    ///
+    /// Ranges are half-open, so a range ending at 11:50 does not overlap one starting at 11:50.
+    /// An empty range, starting and ending at the same time, is the single instant it starts at.
+    pub fn overlaps(&self, other: &TimeRange, buffer: u16) -> bool {
+        let buffer = i32::from(buffer);
+        let start = i32::from(self.start.minutes()) - buffer;
+        let end = i32::from(self.end.minutes()) + buffer;
+        let other_start = i32::from(other.start.minutes());
+        let other_end = i32::from(other.end.minutes());
+        if other_start == other_end {
+            start <= other_start && other_start < end
+        } else {
+            start < other_end && other_start < end
+        }
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {}", self.start, self.end)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// A (de)serializable instructor of a period
+pub struct Instructor {
+    /// The name of the instructor, as listed by the source
+    pub name: String,
+
+    /// Whether this is the primary instructor of the period
+    pub primary: bool,
+}
+
+impl Instructor {
+    /// Creates an instructor, collapsing runs of whitespace in the name. Placeholder names like
+    /// "Staff" or "TBA" give `None`.
+    pub fn new(name: &str, primary: bool) -> Option<Self> {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() || name.eq_ignore_ascii_case("Staff") || name.eq_ignore_ascii_case("TBA")
+        {
+            None
+        } else {
+            Some(Self { name, primary })
+        }
+    }
+}
+
+impl Display for Instructor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.primary {
+            write!(f, "{} (primary)", self.name)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
 }
 
 #[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Debug, Deserialize, Serialize)]
-#[serde(transparent)]
+/// A (de)serializable day of the year, without the year. Dates within a term are ordered
+/// correctly, as no term spans New Year's.
+pub struct CalendarDate {
+    /// The month, 1-12
+    month: u8,
+
+    /// The day of the month, 1-31
+    day: u8,
+}
+
+impl CalendarDate {
+    /// Creates a date, if the month has that many days in at least some years.
+    pub fn new(month: u8, day: u8) -> Option<Self> {
+        let days_in_month = match month {
+            2 => 29,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return None,
+        };
+        if (1..=days_in_month).contains(&day) {
+            Some(Self { month, day })
+        } else {
+            None
+        }
+    }
+
+    /// The date a number of days later, counted in `year`, which decides whether February has 29
+    /// days. Dates run on from 12/31 to 01/01, and 02/29 is read as 03/01 outside leap years.
+    pub fn add_days(self, days: u64, year: i32) -> Self {
+        let date = chrono::NaiveDate::from_ymd_opt(year, self.month.into(), self.day.into())
+            .or_else(|| chrono::NaiveDate::from_ymd_opt(year, 3, 1))
+            .expect("March 1st exists in every year");
+        let date = date + chrono::Days::new(days);
+        Self {
+            month: date.month() as u8,
+            day: date.day() as u8,
+        }
+    }
+}
+
+impl Display for CalendarDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}/{:02}", self.month, self.day)
+    }
+}
+
+impl FromStr for CalendarDate {
+    type Err = CalendarDateParseError;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        from.trim()
+            .split_once('/')
+            .and_then(|(month, day)| Some((u8::from_str(month).ok()?, u8::from_str(day).ok()?)))
+            .and_then(|(month, day)| Self::new(month, day))
+            .ok_or_else(|| CalendarDateParseError(from.into()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CalendarDateParseError(String);
+
+impl Display for CalendarDateParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Input (\"{}\") must be a valid date formatted MM/DD",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for CalendarDateParseError {}
+
+#[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Ord, Debug, Deserialize, Serialize)]
+#[serde(try_from = "u16", into = "u16")]
 pub struct TimeCode {
     time: u16,
 }
 
 impl TimeCode {
+    /// The time at an hour (0-23) and minute (0-59) of the day
+    pub fn new(hour: u16, minute: u16) -> Result<Self, TimeCodeError> {
+        if minute >= 60 {
+            return Err(TimeCodeError::InvalidMinutes);
+        }
+        let time = hour.saturating_mul(100).saturating_add(minute);
+        if VALID_TIME_RANGE.contains(&time) {
+            Ok(Self { time })
+        } else {
+            Err(TimeCodeError::OutOfBounds)
+        }
+    }
+
+    /// The current time on the local clock, to the minute
+    pub fn now() -> Result<Self, TimeCodeError> {
+        let now = chrono::Local::now();
+        Self::new(now.hour() as u16, now.minute() as u16)
+    }
+
+    /// The time a number of minutes after midnight
+    pub fn from_minutes(minutes: u16) -> Result<Self, TimeCodeError> {
+        Self::new(minutes / 60, minutes % 60)
+    }
+
     pub fn time(&self) -> u16 { self.time }
-    pub unsafe fn new_from_int(time_code: u16) -> Self { Self { time: time_code } }
+
+    /// The hour of the day, from 0 to 23
+    pub fn hour(&self) -> u16 { self.time / 100 }
+
+    /// The minute of the hour, from 0 to 59
+    pub fn minute(&self) -> u16 { self.time % 100 }
+
+    /// The number of minutes since midnight
+    pub fn minutes(&self) -> u16 { self.hour() * 60 + self.minute() }
+
+    /// The time a number of minutes later, if it is still a valid time
+    pub fn add_minutes(&self, minutes: u16) -> Result<Self, TimeCodeError> {
+        Self::from_minutes(self.minutes().saturating_add(minutes))
+    }
+
+    /// The time a number of minutes earlier, if it is still a valid time
+    pub fn sub_minutes(&self, minutes: u16) -> Result<Self, TimeCodeError> {
+        self.minutes()
+            .checked_sub(minutes)
+            .ok_or(TimeCodeError::OutOfBounds)
+            .and_then(Self::from_minutes)
+    }
+
+    /// The number of minutes from an earlier time to this one, negative if it is later
+    pub fn minutes_since(&self, earlier: TimeCode) -> i16 {
+        // Both fit easily, as there are only 1440 minutes in a day
+        self.minutes() as i16 - earlier.minutes() as i16
+    }
+
+    /// Formats the time on a 12-hour clock, e.g. "01:35 PM"
+    pub fn format_12h(&self) -> String {
+        let hour = match self.hour() % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        let suffix = if self.hour() < 12 { "AM" } else { "PM" };
+        format!("{:02}:{:02} {}", hour, self.minute(), suffix)
+    }
 }
 
+/// Formats the time on a 24-hour clock, e.g. "13:35"
 impl Display for TimeCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.time / 100, self.time % 100)
+        write!(f, "{:02}:{:02}", self.hour(), self.minute())
     }
 }
 
-impl Into<u16> for TimeCode {
-    fn into(self) -> u16 { self.time }
+impl From<TimeCode> for u16 {
+    fn from(time_code: TimeCode) -> Self { time_code.time() }
 }
 
 impl TryFrom<u16> for TimeCode {
@@ -103,12 +667,7 @@ impl TryFrom<u16> for TimeCode {
 
     fn try_from(value: u16) -> Result<Self, Self::Error> {
         if VALID_TIME_RANGE.contains(&value) {
-            if value % 100 < 60 {
-                // Safety: checked at this point to be valid
-                Ok(unsafe { Self::new_from_int(value) })
-            } else {
-                Err(TimeCodeError::InvalidMinutes)
-            }
+            Self::new(value / 100, value % 100)
         } else {
             Err(TimeCodeError::OutOfBounds)
         }
@@ -145,6 +704,8 @@ pub enum Day {
     Wednesday,
     Thursday,
     Friday,
+    Saturday,
+    Sunday,
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -157,6 +718,49 @@ pub enum PeriodType {
     Other(String),
 }
 
+impl Day {
+    /// Every day of the week, starting on Monday
+    pub const ALL: [Day; 7] = [
+        Self::Monday,
+        Self::Tuesday,
+        Self::Wednesday,
+        Self::Thursday,
+        Self::Friday,
+        Self::Saturday,
+        Self::Sunday,
+    ];
+
+    /// The current day of the week on the local clock
+    pub fn today() -> Self { Self::from(chrono::Local::now().weekday()) }
+}
+
+impl Display for Day {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { Debug::fmt(self, f) }
+}
+
+impl From<chrono::Weekday> for Day {
+    fn from(weekday: chrono::Weekday) -> Self {
+        match weekday {
+            chrono::Weekday::Mon => Self::Monday,
+            chrono::Weekday::Tue => Self::Tuesday,
+            chrono::Weekday::Wed => Self::Wednesday,
+            chrono::Weekday::Thu => Self::Thursday,
+            chrono::Weekday::Fri => Self::Friday,
+            chrono::Weekday::Sat => Self::Saturday,
+            chrono::Weekday::Sun => Self::Sunday,
+        }
+    }
+}
+
+impl Display for PeriodType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Other(other) => write!(f, "{}", other),
+            known => Debug::fmt(known, f),
+        }
+    }
+}
+
 impl<S: AsRef<str>> From<S> for PeriodType {
     fn from(from: S) -> Self {
         match from.as_ref() {
@@ -172,30 +776,30 @@ impl<S: AsRef<str>> From<S> for PeriodType {
 impl FromStr for Day {
     type Err = DayParseError;
 
+    /// Parses a day from its RCOS digit (`0` is Monday), its SIS letter, or its name, which may
+    /// be abbreviated. Letters and names are case-insensitive.
     fn from_str(from: &str) -> Result<Self, Self::Err> {
-        match from {
-            "0" => Ok(Self::Monday),
-            "1" => Ok(Self::Tuesday),
-            "2" => Ok(Self::Wednesday),
-            "3" => Ok(Self::Thursday),
-            "4" => Ok(Self::Friday),
-            "M" => Ok(Self::Monday),
-            "T" => Ok(Self::Tuesday),
-            "W" => Ok(Self::Wednesday),
-            "R" => Ok(Self::Thursday),
-            "F" => Ok(Self::Friday),
-            x => Err(DayParseError(from.into())),
+        match from.trim().to_ascii_lowercase().as_str() {
+            "0" | "m" | "mon" | "monday" => Ok(Self::Monday),
+            "1" | "t" | "tue" | "tues" | "tuesday" => Ok(Self::Tuesday),
+            "2" | "w" | "wed" | "wednesday" => Ok(Self::Wednesday),
+            "3" | "r" | "thu" | "thur" | "thurs" | "thursday" => Ok(Self::Thursday),
+            "4" | "f" | "fri" | "friday" => Ok(Self::Friday),
+            "5" | "s" | "sat" | "saturday" => Ok(Self::Saturday),
+            "6" | "u" | "sun" | "sunday" => Ok(Self::Sunday),
+            _ => Err(DayParseError(from.into())),
         }
     }
 }
 
+#[derive(Clone)]
 pub struct DayParseError(String);
 
 impl Debug for DayParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Input must match /^[01234MTWRF]$/. Provided: {:?}",
+            "Input must match /^[0123456MTWRFSU]$/ or name a day. Provided: {:?}",
             self.0
         )
     }
@@ -205,7 +809,8 @@ impl Display for DayParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Input (\"{}\") must be one of: [M, T, W, R, F] or as digits: [0, 1, 2, 3, 4]",
+            "Input (\"{}\") must be one of: [M, T, W, R, F, S, U], as digits: [0, 1, 2, 3, 4, 5, 6], \
+             or a day name like \"monday\" or \"tue\"",
             self.0
         )
     }
@@ -216,16 +821,50 @@ impl std::error::Error for DayParseError {}
 impl FromStr for TimeCode {
     type Err = TimeCodeParseError;
 
+    /// Parses a time on a 24-hour clock (`1335` or `13:35`), on a 12-hour clock (`1:35pm` or
+    /// `1:35 PM`), or one of the words `noon` and `now`. Letters are case-insensitive.
     fn from_str(from: &str) -> Result<Self, Self::Err> {
-        u16::from_str(from)
-            .map_err(|err| {
-                TimeCodeParseError::new(from.into(), TimeCodeParseErrorKind::ParseIntError(err))
-            })
-            .and_then(|num| {
-                TimeCode::try_from(num).map_err(|a| {
-                    TimeCodeParseError::new(from.into(), TimeCodeParseErrorKind::InvalidTime(a))
-                })
-            })
+        let error = |kind| TimeCodeParseError::new(from.into(), kind);
+        let parse_int = |int: &str| {
+            u16::from_str(int).map_err(|err| error(TimeCodeParseErrorKind::ParseIntError(err)))
+        };
+        let input = from.trim().to_ascii_lowercase();
+        let (clock, pm) = match input.as_str() {
+            "noon" => return Ok(Self { time: 1200 }),
+            "now" => {
+                return Self::now().map_err(|err| error(TimeCodeParseErrorKind::InvalidTime(err)))
+            }
+            input => match input.strip_suffix("am") {
+                Some(clock) => (clock.trim_end(), Some(false)),
+                None => match input.strip_suffix("pm") {
+                    Some(clock) => (clock.trim_end(), Some(true)),
+                    None => (input, None),
+                },
+            },
+        };
+        let (hour, minute) = match clock.split_once(':') {
+            Some((hour, minute)) if minute.len() == 2 => (parse_int(hour)?, parse_int(minute)?),
+            Some(_) => return Err(error(TimeCodeParseErrorKind::Malformed)),
+            // A bare hour on a 12-hour clock, like "1pm"
+            None if pm.is_some() && clock.len() <= 2 => (parse_int(clock)?, 0),
+            None if pm.is_some() => {
+                let time = parse_int(clock)?;
+                (time / 100, time % 100)
+            }
+            // A 24-hour time code, like "1335"
+            None => {
+                return TimeCode::try_from(parse_int(clock)?)
+                    .map_err(|err| error(TimeCodeParseErrorKind::InvalidTime(err)))
+            }
+        };
+        let hour = match pm {
+            Some(_) if !(1..=12).contains(&hour) => {
+                return Err(error(TimeCodeParseErrorKind::Malformed))
+            }
+            Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+            None => hour,
+        };
+        TimeCode::new(hour, minute).map_err(|err| error(TimeCodeParseErrorKind::InvalidTime(err)))
     }
 }
 
@@ -240,7 +879,7 @@ impl Display for TimeCodeParseError {
         write!(
             f,
             "Error parsing time code. Provided: {}. Reason: {}",
-            self.input,
+            self.input(),
             self.kind()
         )
     }
@@ -260,6 +899,7 @@ impl TimeCodeParseError {
 pub enum TimeCodeParseErrorKind {
     InvalidTime(TimeCodeError),
     ParseIntError(ParseIntError),
+    Malformed,
 }
 
 impl Display for TimeCodeParseErrorKind {
@@ -267,8 +907,226 @@ impl Display for TimeCodeParseErrorKind {
         match self {
             Self::ParseIntError(err) => write!(f, "Parsing integer: {}", err),
             Self::InvalidTime(err) => write!(f, "Invalid time: {}", err),
+            Self::Malformed => write!(
+                f,
+                "Expected a time like 1335, 13:35, 1:35pm, 1:35 PM, noon or now"
+            ),
         }
     }
 }
 
 impl std::error::Error for TimeCodeParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_are_formatted_in_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_timestamp(1570528802), "2019-10-08 10:00 UTC");
+        assert_eq!(format_timestamp(1582934400), "2020-02-29 00:00 UTC");
+    }
+
+    fn range(start: u16, end: u16) -> TimeRange {
+        TimeRange {
+            start: TimeCode::try_from(start).unwrap(),
+            end: TimeCode::try_from(end).unwrap(),
+        }
+    }
+
+    #[test]
+    fn period_times_are_both_given_or_absent() {
+        let period = |time: &str| {
+            let fields = r#""instructors": [], "days": [], "location": null, "period_type": null"#;
+            serde_json::from_str::<Period>(&format!("{{{}{}}}", time, fields))
+        };
+        let tba = period("").unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(tba.time, None);
+        let scheduled = period(r#""time_start": 1000, "time_end": 1050, "#)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(scheduled.time, Some(range(1000, 1050)));
+        assert!(period(r#""time_start": 1000, "#).is_err());
+        assert!(period(r#""time_start": "10:00", "time_end": 1050, "#).is_err());
+    }
+
+    #[test]
+    fn touching_ranges_do_not_overlap() {
+        assert!(!range(1000, 1150).overlaps(&range(1150, 1200), 0));
+        assert!(!range(1150, 1200).overlaps(&range(1000, 1150), 0));
+        assert!(range(1000, 1150).overlaps(&range(1149, 1200), 0));
+        assert!(range(1000, 1150).overlaps(&range(900, 1001), 0));
+        assert!(!range(1000, 1150).overlaps(&range(900, 1000), 0));
+    }
+
+    #[test]
+    fn nested_and_identical_ranges_overlap() {
+        assert!(range(1000, 1150).overlaps(&range(1000, 1150), 0));
+        assert!(range(1000, 1150).overlaps(&range(1030, 1100), 0));
+        assert!(range(1030, 1100).overlaps(&range(1000, 1150), 0));
+        assert!(range(700, 2350).overlaps(&range(2349, 2350), 0));
+    }
+
+    #[test]
+    fn instants_are_in_a_range_from_its_start_until_its_end() {
+        assert!(range(1000, 1150).overlaps(&range(1000, 1000), 0));
+        assert!(range(1000, 1150).overlaps(&range(1149, 1149), 0));
+        assert!(!range(1000, 1150).overlaps(&range(1150, 1150), 0));
+        assert!(!range(1000, 1150).overlaps(&range(959, 959), 0));
+    }
+
+    #[test]
+    fn buffers_count_minutes_across_hours() {
+        // 11:50 plus 10 minutes is 12:00, not 11:60
+        assert!(!range(1000, 1150).overlaps(&range(1200, 1300), 10));
+        assert!(range(1000, 1150).overlaps(&range(1159, 1300), 10));
+        assert!(!range(1010, 1150).overlaps(&range(900, 1000), 10));
+        assert!(range(1010, 1150).overlaps(&range(900, 1001), 10));
+        assert!(range(1000, 1150).overlaps(&range(1155, 1155), 10));
+        assert!(!range(1000, 1150).overlaps(&range(1200, 1200), 10));
+        // Buffers may reach past the valid range of times
+        assert!(range(700, 750).overlaps(&range(700, 700), 60));
+        assert!(range(2300, 2350).overlaps(&range(2349, 2349), 60));
+    }
+
+    #[test]
+    fn minutes_are_counted_from_midnight() {
+        assert_eq!(TimeCode::try_from(700).unwrap().minutes(), 420);
+        assert_eq!(TimeCode::try_from(1159).unwrap().minutes(), 719);
+        assert_eq!(TimeCode::try_from(2350).unwrap().minutes(), 1430);
+        assert_eq!(TimeCode::from_minutes(719), TimeCode::try_from(1159));
+        assert_eq!(TimeCode::from_minutes(419), Err(TimeCodeError::OutOfBounds));
+        assert_eq!(range(1150, 1300).minutes(), 70);
+        assert_eq!(range(1300, 1150).minutes(), 0);
+    }
+
+    #[test]
+    fn time_codes_are_built_from_hours_and_minutes() {
+        assert_eq!(TimeCode::new(9, 5).map(|time| time.time()), Ok(905));
+        assert_eq!(TimeCode::new(23, 50).map(|time| time.time()), Ok(2350));
+        assert_eq!(TimeCode::new(6, 59), Err(TimeCodeError::OutOfBounds));
+        assert_eq!(TimeCode::new(23, 51), Err(TimeCodeError::OutOfBounds));
+        assert_eq!(TimeCode::new(12, 60), Err(TimeCodeError::InvalidMinutes));
+        assert_eq!(TimeCode::new(u16::MAX, 0), Err(TimeCodeError::OutOfBounds));
+        assert_eq!(TimeCode::try_from(1160), Err(TimeCodeError::InvalidMinutes));
+        let time = TimeCode::try_from(1335).unwrap();
+        assert_eq!((time.hour(), time.minute()), (13, 35));
+    }
+
+    #[test]
+    fn minutes_are_added_across_hours() {
+        let time = |time| TimeCode::try_from(time).unwrap();
+        assert_eq!(time(1150).add_minutes(50), Ok(time(1240)));
+        assert_eq!(time(1150).add_minutes(10), Ok(time(1200)));
+        assert_eq!(time(1200).sub_minutes(1), Ok(time(1159)));
+        assert_eq!(time(705).sub_minutes(5), Ok(time(700)));
+        assert_eq!(time(705).sub_minutes(6), Err(TimeCodeError::OutOfBounds));
+        assert_eq!(time(2300).add_minutes(50), Ok(time(2350)));
+        assert_eq!(time(2300).add_minutes(51), Err(TimeCodeError::OutOfBounds));
+        assert_eq!(
+            time(2300).add_minutes(u16::MAX),
+            Err(TimeCodeError::OutOfBounds)
+        );
+        assert_eq!(time(1240).minutes_since(time(1150)), 50);
+        assert_eq!(time(1150).minutes_since(time(1240)), -50);
+        assert_eq!(time(2350).minutes_since(time(700)), 16 * 60 + 50);
+    }
+
+    #[test]
+    fn time_codes_are_zero_padded() {
+        let time = |time| TimeCode::try_from(time).unwrap();
+        assert_eq!(time(905).to_string(), "09:05");
+        assert_eq!(time(1335).to_string(), "13:35");
+        assert_eq!(time(905).format_12h(), "09:05 AM");
+        assert_eq!(time(1200).format_12h(), "12:00 PM");
+        assert_eq!(time(1335).format_12h(), "01:35 PM");
+        assert_eq!(time(2350).format_12h(), "11:50 PM");
+        assert_eq!(range(905, 1150).to_string(), "09:05 - 11:50");
+    }
+
+    #[test]
+    fn human_times_are_parsed() {
+        let parse = |input: &str| TimeCode::from_str(input).ok().map(|time| time.time());
+        assert_eq!(parse("1335"), Some(1335));
+        assert_eq!(parse(" 905 "), Some(905));
+        assert_eq!(parse("13:35"), Some(1335));
+        assert_eq!(parse("09:05"), Some(905));
+        assert_eq!(parse("1:35pm"), Some(1335));
+        assert_eq!(parse("1:35 PM"), Some(1335));
+        assert_eq!(parse("1:35 pm"), Some(1335));
+        assert_eq!(parse("135pm"), Some(1335));
+        assert_eq!(parse("10:00 am"), Some(1000));
+        assert_eq!(parse("12:30 pm"), Some(1230));
+        assert_eq!(parse("1pm"), Some(1300));
+        assert_eq!(parse("Noon"), Some(1200));
+        assert_eq!(parse("now").is_some(), TimeCode::now().is_ok());
+    }
+
+    #[test]
+    fn malformed_times_are_rejected() {
+        let kind = |input: &str| TimeCode::from_str(input).map_err(|err| err.kind().clone());
+        assert_eq!(
+            kind("2399"),
+            Err(TimeCodeParseErrorKind::InvalidTime(
+                TimeCodeError::OutOfBounds
+            ))
+        );
+        assert_eq!(
+            kind("11:60"),
+            Err(TimeCodeParseErrorKind::InvalidTime(
+                TimeCodeError::InvalidMinutes
+            ))
+        );
+        assert_eq!(
+            kind("12:30 am"),
+            Err(TimeCodeParseErrorKind::InvalidTime(
+                TimeCodeError::OutOfBounds
+            ))
+        );
+        assert_eq!(kind("13:35 pm"), Err(TimeCodeParseErrorKind::Malformed));
+        assert_eq!(kind("1:5"), Err(TimeCodeParseErrorKind::Malformed));
+        assert!(matches!(
+            kind("half past one"),
+            Err(TimeCodeParseErrorKind::ParseIntError(_))
+        ));
+        assert!(matches!(
+            kind(""),
+            Err(TimeCodeParseErrorKind::ParseIntError(_))
+        ));
+    }
+
+    #[test]
+    fn dates_are_moved_across_months_and_years() {
+        let date = |month, day| CalendarDate::new(month, day).unwrap();
+        assert_eq!(date(9, 2).add_days(2, 2019), date(9, 4));
+        assert_eq!(date(9, 29).add_days(3, 2019), date(10, 2));
+        assert_eq!(date(12, 30).add_days(7, 2019), date(1, 6));
+        assert_eq!(date(2, 27).add_days(2, 2020), date(2, 29));
+        assert_eq!(date(2, 27).add_days(2, 2021), date(3, 1));
+        assert_eq!(date(2, 29).add_days(0, 2021), date(3, 1));
+    }
+
+    #[test]
+    fn out_of_range_times_fail_to_load() {
+        let time = |input: &str| serde_json::from_str::<TimeCode>(input).ok();
+        assert_eq!(time("1000"), TimeCode::try_from(1000).ok());
+        assert_eq!(time("600"), None);
+        assert_eq!(time("2400"), None);
+        assert_eq!(time("1075"), None);
+        assert_eq!(
+            serde_json::to_string(&TimeCode::try_from(1335).unwrap()).unwrap(),
+            "1335"
+        );
+    }
+
+    #[test]
+    fn section_ids_keep_numeric_order() {
+        let mut ids = ["10", "H01", "2", "L1", "01", "T", "02"]
+            .iter()
+            .map(SectionId::from)
+            .collect::<Vec<_>>();
+        ids.sort();
+        let ids = ids.iter().map(SectionId::to_string).collect::<Vec<_>>();
+        assert_eq!(ids, ["01", "02", "2", "10", "H01", "L1", "T"]);
+    }
+}
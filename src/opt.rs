@@ -22,7 +22,10 @@
 // );
 //
 
-use crate::models::{Day, TimeCode};
+use crate::{
+    actions::CourseFilter,
+    models::{CalendarDate, Campus, Day, GradeType, TimeCode},
+};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -31,21 +34,130 @@ pub fn parse_args() -> AppWhereIsClass { AppWhereIsClass::from_args() }
 #[derive(StructOpt, Debug)]
 #[structopt(name = "whereisclass")]
 pub enum AppWhereIsClass {
+    /// Parse an HTML file containing just a table of all classes formatted like SIS into JSON
     ParseHtml(ParseArgs),
+    /// Parse an RCOS XML file into JSON
     #[cfg(feature = "rcosxml")]
     ParseRcos(ParseArgs),
+    /// Determines which courses are being held in a given room at a given time
     FindCourseInRoom {
+        /// The JSON Course DB to scan
         db: PathBuf,
+        /// The SIS room name (e.g. "SAGE 3205")
         room: String,
+        /// The time, like 1335, 13:35, 1:35pm, noon or now
         time: TimeCode,
-        day: Day,
+        /// The day, one of [M, T, W, R, F, S, U] or a name like "monday" or "tue". Defaults to
+        /// today
+        day: Option<Day>,
+        /// The date (MM/DD), to ignore periods that are not held that week
+        #[structopt(long)]
+        date: Option<CalendarDate>,
+        /// Minutes of passing time to keep free before and after each period, at most 1440
+        #[structopt(long, default_value = "0", parse(try_from_str = parse_buffer))]
+        buffer: u16,
     },
+    /// Find empty rooms for a given time range, and how long they stay free around it
     EmptyRooms {
+        /// The JSON Course DB to scan
         db: PathBuf,
+        /// The start time, like 1335, 13:35, 1:35pm, noon or now
         time_start: TimeCode,
+        /// The end time
         time_end: TimeCode,
-        day: Day,
+        /// The day, one of [M, T, W, R, F, S, U] or a name like "monday" or "tue". Defaults to
+        /// today
+        day: Option<Day>,
+        /// The date (MM/DD), to ignore periods that are not held that week
+        #[structopt(long)]
+        date: Option<CalendarDate>,
+        /// Only consider rooms used by sections on this campus (e.g. T or Troy)
+        #[structopt(long, parse(from_str))]
+        campus: Option<Campus>,
+        /// Minutes of passing time to keep free before and after each period, at most 1440
+        #[structopt(long, default_value = "0", parse(try_from_str = parse_buffer))]
+        buffer: u16,
     },
+    /// Find every interval during a day that a room stays free for at least a given length
+    FreeSlots(FreeSlotsArgs),
+    /// List the courses matching the given filters
+    FindCourses {
+        /// The JSON Course DB to scan
+        db: PathBuf,
+        #[structopt(flatten)]
+        filter: CourseFilterArgs,
+    },
+    /// Find where and when a course meets, by its department and number, a CRN or its title
+    WhereIs {
+        /// The JSON Course DB to scan
+        db: PathBuf,
+        /// The course (e.g. "CSCI 1200"), a CRN (e.g. 81047) or words from its title (e.g. "data
+        /// struct")
+        #[structopt(required = true)]
+        query: Vec<String>,
+        /// Only show the meeting in progress on this day, or the next one. Defaults to today if
+        /// --time is given
+        #[structopt(long)]
+        day: Option<Day>,
+        /// Only show the meeting in progress at this time, or the next one. Defaults to now if
+        /// --day is given
+        #[structopt(long)]
+        time: Option<TimeCode>,
+        /// The date (MM/DD) of the day, to ignore periods on the days they are not held
+        #[structopt(long)]
+        date: Option<CalendarDate>,
+    },
+    /// Show the weekly timetable of an instructor, or where they teach at a given time or next
+    Instructor {
+        /// The JSON Course DB to scan
+        db: PathBuf,
+        /// The name of the instructor, or the start of any part of it (e.g. "cutler")
+        #[structopt(required = true)]
+        name: Vec<String>,
+        /// Only show the meeting in progress on this day, or the next one. Defaults to today if
+        /// --time is given
+        #[structopt(long)]
+        day: Option<Day>,
+        /// Only show the meeting in progress at this time, or the next one. Defaults to now if
+        /// --day is given
+        #[structopt(long)]
+        time: Option<TimeCode>,
+        /// The date (MM/DD) of the day, to ignore periods on the days they are not held
+        #[structopt(long)]
+        date: Option<CalendarDate>,
+    },
+    /// List the sections a section is crosslisted with
+    Crosslisted {
+        /// The JSON Course DB to scan
+        db: PathBuf,
+        /// The CRN of the section
+        crn: u32,
+    },
+    /// List the sections that are still missing a meeting time, days or room
+    Unscheduled {
+        /// The JSON Course DB to scan
+        db: PathBuf,
+        /// Only list sections of courses in this department (e.g. CSCI)
+        #[structopt(long)]
+        dept: Option<String>,
+    },
+    /// Upgrade a JSON Course DB written by an older version of whereisclass, in place
+    Migrate {
+        /// The JSON Course DB to upgrade
+        db: PathBuf,
+    },
+}
+
+/// The longest buffer accepted, a whole day in minutes
+const MAX_BUFFER: u16 = 24 * 60;
+
+/// Reads a buffer, which cannot be longer than [`MAX_BUFFER`]
+fn parse_buffer(from: &str) -> Result<u16, String> {
+    match from.parse::<u16>() {
+        Ok(buffer) if buffer <= MAX_BUFFER => Ok(buffer),
+        Ok(_) => Err(format!("must be at most {} minutes", MAX_BUFFER)),
+        Err(err) => Err(err.to_string()),
+    }
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -54,9 +166,97 @@ pub struct ParseArgs {
     #[structopt(short, long)]
     pub force: bool,
 
+    /// Fail on the first malformed entry instead of skipping it
+    #[structopt(short, long)]
+    pub strict: bool,
+
     /// Input file to parse
     pub file: PathBuf,
 
     /// Output file to write, will not overwrite unless --force is specified
     pub output: PathBuf,
 }
+
+#[derive(StructOpt, Debug, Clone)]
+pub struct FreeSlotsArgs {
+    /// The JSON Course DB to scan
+    pub db: PathBuf,
+
+    /// The earliest time a slot may start, like 1335, 13:35, 1:35pm, noon or now
+    pub earliest: TimeCode,
+
+    /// The latest time a slot may end
+    pub latest: TimeCode,
+
+    /// The minimum length of a slot, in minutes
+    pub min_length: u16,
+
+    /// The day, one of [M, T, W, R, F, S, U] or a name like "monday" or "tue". Defaults to today
+    pub day: Option<Day>,
+
+    /// The date (MM/DD), to ignore periods that are not held that week
+    #[structopt(long)]
+    pub date: Option<CalendarDate>,
+
+    /// Only consider rooms used by sections on this campus (e.g. T or Troy)
+    #[structopt(long, parse(from_str))]
+    pub campus: Option<Campus>,
+
+    /// Minutes of passing time to keep free before and after each period, at most 1440
+    #[structopt(long, default_value = "0", parse(try_from_str = parse_buffer))]
+    pub buffer: u16,
+
+    /// List the longest slots first, instead of by room
+    #[structopt(long)]
+    pub longest_first: bool,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub struct CourseFilterArgs {
+    /// Only list courses in this department (e.g. CSCI)
+    #[structopt(long)]
+    pub dept: Option<String>,
+
+    /// Only list courses that can be taken for this many credit hours
+    #[structopt(long)]
+    pub credits: Option<u8>,
+
+    /// Only list courses graded this way, one of [letter, s/u, non-graded]
+    #[structopt(long)]
+    pub grade_type: Option<GradeType>,
+
+    /// Only list sections on this campus (e.g. T or Troy)
+    #[structopt(long, parse(from_str))]
+    pub campus: Option<Campus>,
+
+    /// Only list sections with this attribute, by name or initials (e.g. CI)
+    #[structopt(long)]
+    pub attribute: Option<String>,
+
+    /// Only list sections meeting on this day
+    #[structopt(long)]
+    pub day: Option<Day>,
+
+    /// Only list sections meeting no earlier than this time
+    #[structopt(long)]
+    pub starts_after: Option<TimeCode>,
+
+    /// Only list sections meeting no later than this time
+    #[structopt(long)]
+    pub ends_before: Option<TimeCode>,
+}
+
+impl From<CourseFilterArgs> for CourseFilter {
+    fn from(args: CourseFilterArgs) -> Self {
+        CourseFilter {
+            dept: args.dept,
+            credits: args.credits,
+            grade_type: args.grade_type,
+            campus: args.campus,
+            attribute: args.attribute,
+            day: args.day,
+            starts_after: args.starts_after,
+            ends_before: args.ends_before,
+        }
+    }
+}
@@ -2,11 +2,10 @@
 
 use crate::{
     models::*,
-    parser::{CourseDBParseError, CourseDBParser},
+    parser::{CourseDBParseError, CourseDBParser, ParsedCourseDB, SourceLocation},
 };
 use lazy_static::lazy_static;
-use regex::Regex;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use std::{
     fmt::{Display, Formatter},
     num::ParseIntError,
@@ -16,6 +15,9 @@ use std::{
 struct Selectors {
     pub tr: Selector,
     pub td: Selector,
+    pub header: Selector,
+    pub abbr: Selector,
+    pub term: Selector,
 }
 
 lazy_static! {
@@ -23,132 +25,475 @@ lazy_static! {
         Selectors {
             tr: Selector::parse("tr").unwrap(),
             td: Selector::parse("td").unwrap(),
+            header: Selector::parse("th.ddheader").unwrap(),
+            abbr: Selector::parse("abbr, acronym").unwrap(),
+            term: Selector::parse(r#"input[name="assoc_term_in"]"#).unwrap(),
         }
     };
-    static ref DAY_REGEX: Regex = { Regex::new(r"^[MTWRF]*$").unwrap() };
 }
 
-pub struct HtmlParser;
+/// The most columns a single cell may span, to bound the work done on malformed input
+const MAX_COLSPAN: usize = 64;
 
-impl CourseDBParser for HtmlParser {
-    fn parse(&self, input: &str) -> Result<CourseDB, CourseDBParseError> {
-        Ok(parse_html_lossy(input))
-    }
+/// Parses an HTML SIS listing table.
+///
+/// In strict mode, the first malformed row fails the whole parse. Otherwise, malformed rows are
+/// skipped and returned as warnings, and unknown days are left out of a meeting that is otherwise
+/// kept.
+pub struct HtmlParser {
+    pub strict: bool,
 }
 
-fn parse_html_lossy(input: &str) -> CourseDB {
-    let mut db = CourseDB {
-        courses: Vec::new(),
-    };
+impl CourseDBParser for HtmlParser {
+    fn parse(&self, input: &str) -> Result<ParsedCourseDB, CourseDBParseError> {
+        let mut db = CourseDB {
+            semester: Semester::default(),
+            courses: Vec::new(),
+            crosslistings: Vec::new(),
+        };
+        let mut warnings = Vec::new();
 
-    let fragment = input.replace("\n", "");
+        let fragment = input.replace("\n", "");
 
-    let html = Html::parse_fragment(fragment.as_str());
+        let html = Html::parse_fragment(fragment.as_str());
 
-    let mut last_course = 0usize;
+        // Every row repeats the term it is listed for, so the first one names the whole table
+        let term = html
+            .select(&SELECTORS.term)
+            .find_map(|input| input.value().attr("value"))
+            .and_then(|term| u32::from_str(term.trim()).ok());
+        db.semester = Semester {
+            term,
+            description: term.and_then(describe_term),
+            source: Some(SourceFormat::SisHtml),
+            timestamp: None,
+        };
 
-    for tr in html.select(&SELECTORS.tr) {
-        let tds = tr.select(&SELECTORS.td).collect::<Vec<_>>();
-        if tds.len() == 0 {
-            continue;
-        }
-        if tds.len() < 22 {
-            eprintln!("Unexpected HTML at {}", tr.html());
+        let mut columns = None;
+        let mut skipped = Vec::new();
+
+        for (row, tr) in html.select(&SELECTORS.tr).enumerate() {
+            let ths = tr.select(&SELECTORS.header).collect::<Vec<_>>();
+            // Cells spanning several columns are repeated, so that indices line up with the header
+            let tds = tr
+                .select(&SELECTORS.td)
+                .flat_map(|td| {
+                    let span = td
+                        .value()
+                        .attr("colspan")
+                        .and_then(|span| usize::from_str(span).ok())
+                        .unwrap_or(1);
+                    std::iter::repeat_n(td, span.clamp(1, MAX_COLSPAN))
+                })
+                .collect::<Vec<_>>();
+            let result = if !ths.is_empty() {
+                // Every department's block of the table repeats the header
+                let map = ColumnMap::from_header(&ths);
+                columns = map.as_ref().ok().cloned();
+                map.map(|_| ())
+            } else if tds.is_empty() {
+                continue;
+            } else {
+                columns
+                    .as_ref()
+                    .ok_or((None, CourseDBHTMLParseError::NoHeader))
+                    .and_then(|columns| parse_row(&mut db, columns, &tds, &mut skipped))
+            };
+            for (field, cause) in skipped.drain(..).chain(result.err()) {
+                let err = CourseDBParseError::new(SourceLocation::TableRow(row + 1), field, cause);
+                if self.strict {
+                    return Err(err);
+                }
+                warnings.push(err);
+            }
         }
-        let section_str = tds[4].text().next().unwrap_or("00");
-        if section_str == "H01" {
-            continue;
+        Ok(ParsedCourseDB { db, warnings })
+    }
+}
+
+/// The index of each column of interest in a row, as read from the table's header row
+#[derive(Clone, Debug)]
+struct ColumnMap {
+    crn: usize,
+    subject: usize,
+    course: usize,
+    section: usize,
+    title: usize,
+    days: usize,
+    time: usize,
+    instructor: usize,
+    location: usize,
+    credits: Option<usize>,
+    dates: Option<usize>,
+    campus: Option<usize>,
+    attributes: Option<usize>,
+    seats: Option<SeatColumns>,
+    waitlist: Option<SeatColumns>,
+    crosslist: Option<SeatColumns>,
+}
+
+/// The indices of the capacity, actual and remaining columns of a group of seat counts
+#[derive(Copy, Clone, Debug)]
+struct SeatColumns {
+    capacity: (&'static str, usize),
+    actual: (&'static str, usize),
+    remaining: (&'static str, usize),
+}
+
+impl SeatColumns {
+    /// Parses the seat counts of a row, which may be left blank.
+    fn parse(&self, tds: &[ElementRef]) -> Result<Option<SeatCount>, RowError> {
+        let blank = [self.capacity, self.actual, self.remaining]
+            .iter()
+            .all(|(_, idx)| tds[*idx].text().collect::<String>().trim().is_empty());
+        if blank {
+            return Ok(None);
         }
-        let course = if section_str == "01" {
-            // new course
-            db.courses.push(Course {
-                name: tds[7].text().next().unwrap().to_string(),
-                dept: tds[2].text().next().unwrap().to_string(),
-                num: u16::from_str(tds[3].text().next().unwrap()).unwrap(),
-                sections: vec![],
-            });
-            last_course = db.courses.len() - 1;
-            db.courses.get_mut(last_course).unwrap()
-        } else {
-            db.courses.get_mut(last_course).unwrap()
+        Ok(Some(SeatCount {
+            capacity: parse_cell(&tds[self.capacity.1], self.capacity.0)?,
+            actual: parse_cell(&tds[self.actual.1], self.actual.0)?,
+            remaining: parse_cell(&tds[self.remaining.1], self.remaining.0)?,
+        }))
+    }
+
+    fn max(&self) -> usize { self.capacity.1.max(self.actual.1).max(self.remaining.1) }
+}
+
+impl ColumnMap {
+    /// Builds the map from the `<th class="ddheader">` cells of a header row. Each column is
+    /// identified by the title of its ABBR/ACRONYM, or by its text.
+    fn from_header(ths: &[ElementRef]) -> Result<Self, RowError> {
+        let headers = ths
+            .iter()
+            .map(|th| {
+                let title = th
+                    .select(&SELECTORS.abbr)
+                    .next()
+                    .and_then(|abbr| abbr.value().attr("title"))
+                    .map(|title| title.trim().to_string());
+                let text = th.text().collect::<String>().trim().to_string();
+                (title, text)
+            })
+            .collect::<Vec<_>>();
+        let find = |field: &'static str, title: &str| {
+            headers
+                .iter()
+                .position(|(abbr_title, text)| {
+                    abbr_title
+                        .as_deref()
+                        .is_some_and(|abbr_title| abbr_title.eq_ignore_ascii_case(title))
+                        || text.eq_ignore_ascii_case(field)
+                })
+                .ok_or((Some(field), CourseDBHTMLParseError::MissingColumn(field)))
         };
-        let section_len = course.sections.len();
-        let is_period = !section_str.as_bytes()[0].is_ascii_digit();
-        let section = if is_period {
-            course.sections.get_mut(section_len - 1).unwrap()
-        } else {
-            course.sections.push(Section {
-                crn: u32::from_str(tds[1].text().next().unwrap()).expect("Reading u32 for crn"),
-                num: u8::from_str(section_str).expect("Reading section"),
-                notes: vec![],
-                periods: vec![],
-            });
-            course.sections.get_mut(section_len).unwrap()
+        let seats = |prefix: &str, cap: &'static str, act: &'static str, rem: &'static str| {
+            let found = |field, kind| {
+                find(field, format!("{} {}", prefix, kind).as_str())
+                    .ok()
+                    .map(|idx| (field, idx))
+            };
+            Some(SeatColumns {
+                capacity: found(cap, "Capacity")?,
+                actual: found(act, "Actual")?,
+                remaining: found(rem, "Remaining")?,
+            })
         };
+        Ok(Self {
+            seats: seats("Section", "Cap", "Act", "Rem"),
+            waitlist: seats("Waitlist", "WL Cap", "WL Act", "WL Rem"),
+            crosslist: seats("Crosslist", "XL Cap", "XL Act", "XL Rem"),
+            crn: find("CRN", "Course Reference Number")?,
+            subject: find("Subj", "Subject")?,
+            course: find("Crse", "Course")?,
+            section: find("Sec", "Section")?,
+            title: find("Title", "Title")?,
+            days: find("Days", "Days")?,
+            time: find("Time", "Time")?,
+            instructor: find("Instructor", "Instructor")?,
+            location: find("Location", "Location")?,
+            credits: find("Cred", "Credit Hours").ok(),
+            dates: find("Date (MM/DD)", "Date").ok(),
+            campus: find("Cmp", "Campus").ok(),
+            attributes: find("Attribute", "Attribute").ok(),
+        })
+    }
 
-        let day_str = match tds[8]
-            .text()
-            .filter(|k| k != &"TBA" && DAY_REGEX.is_match(k))
-            .next()
-        {
-            Some(s) => s,
-            None => {
-                continue;
-            }
-        };
-        let days = day_str
-            .chars()
-            .map(|c| format!("{}", c))
-            .map(|c| Day::from_str(c.as_str()))
-            .collect::<Result<Vec<_>, DayParseError>>();
+    /// The number of cells a row needs to contain every mapped column
+    fn len(&self) -> usize {
+        [
+            self.crn,
+            self.subject,
+            self.course,
+            self.section,
+            self.title,
+            self.days,
+            self.time,
+            self.instructor,
+            self.location,
+        ]
+        .iter()
+        .copied()
+        .chain(self.credits)
+        .chain(self.dates)
+        .chain(self.campus)
+        .chain(self.attributes)
+        .chain(
+            [&self.seats, &self.waitlist, &self.crosslist]
+                .iter()
+                .filter_map(|seats| seats.as_ref().map(SeatColumns::max)),
+        )
+        .max()
+        .unwrap()
+            + 1
+    }
+}
 
-        let days = match days {
-            Ok(days) => days,
-            Err(err) => {
-                eprintln!("Failed to parse days: \"{}\". Reason: {}", day_str, err);
-                continue;
-            }
-        };
+/// A failure to parse a row, along with the name of the column at fault, if any
+type RowError = (Option<&'static str>, CourseDBHTMLParseError);
 
-        if days.len() != 0 {
-            let time = tds[9].text().next().unwrap();
-            if time == "TBA" {
-                continue;
-            }
-            // let times: Vec<&str> = time.split("-").collect::<Vec<_>>();
-            // assert_eq!(2, times.len(), "Not two times (start/end) :: {:?}", times);
-            // let start = parse_time(times[0]);
-            // let end = parse_time(times[1]);
-            let (start, end) = match try_parse_time_range(time) {
-                Ok(v) => v,
+/// Gets the first text node of a cell, failing if the cell is empty.
+fn cell_text<'a>(td: &ElementRef<'a>, field: &'static str) -> Result<&'a str, RowError> {
+    td.text()
+        .next()
+        .ok_or((Some(field), CourseDBHTMLParseError::MissingValue))
+}
+
+fn parse_cell<T: FromStr<Err = ParseIntError>>(
+    td: &ElementRef,
+    field: &'static str,
+) -> Result<T, RowError> {
+    T::from_str(cell_text(td, field)?.trim())
+        .map_err(|err| (Some(field), CourseDBHTMLParseError::ParseIntErr(err)))
+}
+
+/// Names a term code like `202001` after the season and year it starts in, like "Spring 2020"
+fn describe_term(term: u32) -> Option<String> {
+    let season = match term % 100 {
+        1 => "Spring",
+        5 => "Summer",
+        9 => "Fall",
+        _ => return None,
+    };
+    Some(format!("{} {}", season, term / 100))
+}
+
+/// Parses an instructor cell, like `Zackary B.  Davis (P), Jennifer Lynn  Fredericks`, where the
+/// primary instructor is marked with `(P)`.
+fn parse_instructors(td: &ElementRef) -> Vec<Instructor> {
+    td.text()
+        .collect::<String>()
+        .split(',')
+        .filter_map(|name| match name.trim().strip_suffix("(P)") {
+            Some(primary) => Instructor::new(primary, true),
+            None => Instructor::new(name, false),
+        })
+        .collect()
+}
+
+/// Parses a credit hours cell, like `4.000` or `1.000-6.000`, which may be left blank.
+fn parse_credits(td: &ElementRef) -> Result<Option<CreditRange>, RowError> {
+    let text = td.text().collect::<String>();
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let malformed = || {
+        (
+            Some("Cred"),
+            CourseDBHTMLParseError::MalformedCredits(text.into()),
+        )
+    };
+    // Credit hours are whole numbers, but listed with three decimal places
+    let parse = |credits: &str| {
+        let (whole, fraction) = credits
+            .trim()
+            .split_once('.')
+            .unwrap_or((credits.trim(), ""));
+        if fraction.chars().all(|c| c == '0') {
+            u8::from_str(whole).map_err(|_| malformed())
+        } else {
+            Err(malformed())
+        }
+    };
+    let (min, max) = match text.split_once('-') {
+        Some((min, max)) => (parse(min)?, parse(max)?),
+        None => {
+            let credits = parse(text)?;
+            (credits, credits)
+        }
+    };
+    if min <= max {
+        Ok(Some(CreditRange { min, max }))
+    } else {
+        Err(malformed())
+    }
+}
+
+/// Parses a date range cell, like `01/13-05/08`, which may be left blank.
+fn parse_dates(td: &ElementRef) -> Result<(Option<CalendarDate>, Option<CalendarDate>), RowError> {
+    let text = td.text().collect::<String>();
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok((None, None));
+    }
+    text.split_once('-')
+        .and_then(|(start, end)| {
+            let start = CalendarDate::from_str(start).ok()?;
+            let end = CalendarDate::from_str(end).ok()?;
+            (start <= end).then_some((Some(start), Some(end)))
+        })
+        .ok_or_else(|| {
+            (
+                Some("Date (MM/DD)"),
+                CourseDBHTMLParseError::MalformedDates(text.into()),
+            )
+        })
+}
+
+/// Parses an attribute cell, which lists attributes separated by "and".
+fn parse_attributes(td: &ElementRef) -> Vec<String> {
+    td.text()
+        .collect::<String>()
+        .split(" and ")
+        .map(str::trim)
+        .filter(|attribute| !attribute.is_empty())
+        .map(|attribute| attribute.to_string())
+        .collect()
+}
+
+fn parse_seats(
+    columns: &Option<SeatColumns>,
+    tds: &[ElementRef],
+) -> Result<Option<SeatCount>, RowError> {
+    columns
+        .as_ref()
+        .map_or(Ok(None), |columns| columns.parse(tds))
+}
+
+/// Parses a single row of the table into `db`. A row either starts a new course, adds a section
+/// to the last course, or adds a period to the last section. A row that fails to parse leaves
+/// `db` untouched. Parts of a row that are dropped without rejecting it are added to `skipped`.
+fn parse_row(
+    db: &mut CourseDB,
+    columns: &ColumnMap,
+    tds: &[ElementRef],
+    skipped: &mut Vec<RowError>,
+) -> Result<(), RowError> {
+    if tds.len() < columns.len() {
+        return Err((None, CourseDBHTMLParseError::TooFewCells(tds.len())));
+    }
+    // Rows that only add another meeting to the section above them leave the CRN blank
+    let is_period = tds[columns.crn]
+        .text()
+        .collect::<String>()
+        .trim()
+        .is_empty();
+    if is_period {
+        let section = db
+            .courses
+            .last_mut()
+            .ok_or((Some("CRN"), CourseDBHTMLParseError::NoCourse))?
+            .sections
+            .last_mut()
+            .ok_or((Some("CRN"), CourseDBHTMLParseError::NoSection))?;
+        section.periods.push(parse_period(columns, tds, skipped)?);
+        return Ok(());
+    }
+
+    let dept = cell_text(&tds[columns.subject], "Subj")?.trim();
+    let num = parse_cell(&tds[columns.course], "Crse")?;
+    let section_id = tds[columns.section].text().collect::<String>();
+    if section_id.trim().is_empty() {
+        return Err((Some("Sec"), CourseDBHTMLParseError::MissingValue));
+    }
+    let section = Section {
+        crn: parse_cell(&tds[columns.crn], "CRN")?,
+        num: SectionId::from(section_id),
+        notes: vec![],
+        periods: vec![parse_period(columns, tds, skipped)?],
+        seats: parse_seats(&columns.seats, tds)?,
+        waitlist: parse_seats(&columns.waitlist, tds)?,
+        crosslist: parse_seats(&columns.crosslist, tds)?,
+        campus: columns
+            .campus
+            .map(|idx| tds[idx].text().collect::<String>())
+            .filter(|campus| !campus.trim().is_empty())
+            .map(Campus::from),
+        attributes: columns
+            .attributes
+            .map(|idx| parse_attributes(&tds[idx]))
+            .unwrap_or_default(),
+    };
+    match db.courses.last_mut() {
+        Some(course) if course.dept == dept && course.num == num => course.sections.push(section),
+        _ => db.courses.push(Course {
+            name: cell_text(&tds[columns.title], "Title")?.to_string(),
+            dept: dept.to_string(),
+            num,
+            sections: vec![section],
+            credits: columns
+                .credits
+                .map(|idx| parse_credits(&tds[idx]))
+                .transpose()?
+                .flatten(),
+            grade_type: None,
+        }),
+    }
+    Ok(())
+}
+
+/// Parses the meeting described by a row. Unknown days are added to `skipped` and left out, so
+/// that the meeting is still listed on the days that could be read.
+fn parse_period(
+    columns: &ColumnMap,
+    tds: &[ElementRef],
+    skipped: &mut Vec<RowError>,
+) -> Result<Period, RowError> {
+    // Blank or "TBA" days and times are still to be announced, and leave the period unscheduled
+    let day_str = tds[columns.days].text().collect::<String>();
+    let day_str = day_str.trim();
+    let days = if day_str == "TBA" {
+        vec![]
+    } else {
+        day_str
+            .chars()
+            .map(|c| format!("{}", c))
+            .filter_map(|c| match Day::from_str(c.as_str()) {
+                Ok(day) => Some(day),
                 Err(err) => {
-                    eprintln!("Failed to parse time range: \"{}\" - {}", time, err);
-                    continue;
-                }
-            };
-            let period_type = None;
-            let prof = tds[19]
-                .text()
-                .next()
-                .unwrap()
-                .replace("   ", " ")
-                .replace(" (", "");
-            let loc = tds[21].text().next().unwrap();
-            section.periods.push(Period {
-                time_start: start,
-                time_end: end,
-                period_type,
-                location: if loc.trim().len() == 0 {
+                    skipped.push((Some("Days"), CourseDBHTMLParseError::Days(err)));
                     None
-                } else {
-                    Some(loc.to_string())
-                },
-                instructor: prof,
-                days,
+                }
             })
-        }
-    }
-    db
+            .collect()
+    };
+    let time_str = tds[columns.time].text().collect::<String>();
+    let time_str = time_str.trim();
+    let time = if time_str.is_empty() || time_str == "TBA" {
+        None
+    } else {
+        let (start, end) = try_parse_time_range(time_str).map_err(|err| (Some("Time"), err))?;
+        Some(TimeRange { start, end })
+    };
+    let location = tds[columns.location].text().collect::<String>();
+    let location = location.trim();
+    let (date_start, date_end) = match columns.dates {
+        Some(idx) => parse_dates(&tds[idx])?,
+        None => (None, None),
+    };
+    Ok(Period {
+        time,
+        period_type: None,
+        location: if location.is_empty() || location == "TBA" {
+            None
+        } else {
+            Some(location.to_string())
+        },
+        instructors: parse_instructors(&tds[columns.instructor]),
+        days,
+        date_start,
+        date_end,
+    })
 }
 
 fn try_parse_time_range(s: &str) -> Result<(TimeCode, TimeCode), CourseDBHTMLParseError> {
@@ -162,46 +507,31 @@ fn try_parse_time_range(s: &str) -> Result<(TimeCode, TimeCode), CourseDBHTMLPar
 }
 
 fn try_parse_time(s: &str) -> Result<TimeCode, CourseDBHTMLParseError> {
-    let spls = s.split(" ").collect::<Vec<_>>();
-    if spls.len() == 2 {
-        let pm = match spls[1] {
-            "am" => Ok(false),
-            "pm" => Ok(true),
-            _ => Err(CourseDBHTMLParseError::MalformedTime(
-                MalformedTimeKind::MalformedAMPM,
-            )),
-        };
-        pm.and_then(|pm| {
-            let time_cleaned = spls[0]
-                .chars()
-                .filter(|c| c.is_ascii_digit())
-                .collect::<String>();
-            let initial_timecode = u16::from_str(time_cleaned.as_str())
-                .map_err(|err| CourseDBHTMLParseError::ParseIntErr(err));
-            initial_timecode.map(|timecode| (pm, timecode))
-        })
-        .and_then(|(pm, timecode)| {
-            let timecode = if pm && timecode < 1200 {
-                timecode + 1200
-            } else {
-                timecode
-            };
-            TimeCode::try_from(timecode).map_err(|err| {
-                CourseDBHTMLParseError::TimeCodeParseError(TimeCodeParseError::new(
-                    spls[0].into(),
-                    TimeCodeParseErrorKind::InvalidTime(err),
-                ))
-            })
-        })
-    } else {
-        Err(CourseDBHTMLParseError::MalformedTime(
+    // SIS always lists times on a 12-hour clock, like "1:35 pm"
+    match s.trim().rsplit_once(' ') {
+        Some((_, "am" | "pm")) => {
+            TimeCode::from_str(s).map_err(CourseDBHTMLParseError::TimeCodeParseError)
+        }
+        Some(_) => Err(CourseDBHTMLParseError::MalformedTime(
+            MalformedTimeKind::MalformedAMPM,
+        )),
+        None => Err(CourseDBHTMLParseError::MalformedTime(
             MalformedTimeKind::MissingAMPM,
-        ))
+        )),
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum CourseDBHTMLParseError {
+    NoHeader,
+    MissingColumn(&'static str),
+    MalformedCredits(String),
+    MalformedDates(String),
+    TooFewCells(usize),
+    MissingValue,
+    NoCourse,
+    NoSection,
+    Days(DayParseError),
     NotTwoTimes,
     MalformedTime(MalformedTimeKind),
     TimeCodeParseError(TimeCodeParseError),
@@ -211,6 +541,19 @@ pub enum CourseDBHTMLParseError {
 impl Display for CourseDBHTMLParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::NoHeader => write!(f, "Row is not preceded by a valid header row"),
+            Self::MissingColumn(column) => {
+                write!(f, "Header row has no \"{}\" column", column)
+            }
+            Self::MalformedCredits(credits) => {
+                write!(f, "Credit hours are malformed: \"{}\"", credits)
+            }
+            Self::MalformedDates(dates) => write!(f, "Dates are malformed: \"{}\"", dates),
+            Self::TooFewCells(cells) => write!(f, "Row is missing cells, found only {}", cells),
+            Self::MissingValue => write!(f, "Cell is empty"),
+            Self::NoCourse => write!(f, "Row continues a course, but no course precedes it"),
+            Self::NoSection => write!(f, "Row continues a section, but no section precedes it"),
+            Self::Days(err) => write!(f, "Days failed to parse: [{}]", err),
             Self::NotTwoTimes => write!(f, "Two times should be present"),
             Self::MalformedTime(mal) => write!(f, "Time is malformed: [{}]", mal),
             Self::TimeCodeParseError(err) => write!(f, "Time code failed to parse: [{}]", err),
@@ -233,3 +576,350 @@ impl Display for MalformedTimeKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::FindUnscheduled;
+    use std::fs;
+
+    fn read_table() -> String {
+        fs::read_to_string(format!(
+            "{}/raw-input/sistable.html",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .expect("Reading raw input")
+    }
+
+    /// The start of the SIS table, cut off after the given number of rows.
+    fn table_prefix(rows: usize) -> String {
+        let table = read_table();
+        let end = table
+            .match_indices("</tr>")
+            .nth(rows - 1)
+            .map(|(idx, tr)| idx + tr.len())
+            .unwrap_or(table.len());
+        format!("{}</table>", &table[..end])
+    }
+
+    /// A tiny deterministic xorshift generator, so that failures are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize { (self.next() % bound as u64) as usize }
+    }
+
+    fn mutate(input: &str, rng: &mut Rng) -> String {
+        const NOISE: &[&str] = &[
+            "<", ">", "</td>", "<td>", "</tr>", "<tr>", "-", " ", "pm", "TBA", "9", "0", "H",
+            "&nbsp;",
+        ];
+        let mut chars = input.chars().collect::<Vec<_>>();
+        for _ in 0..1 + rng.below(8) {
+            let at = rng.below(chars.len());
+            match rng.below(3) {
+                0 => {
+                    let len = rng.below(64).min(chars.len() - at);
+                    chars.drain(at..at + len);
+                }
+                1 => {
+                    let noise = NOISE[rng.below(NOISE.len())];
+                    chars.splice(at..at, noise.chars());
+                }
+                _ => {
+                    let len = rng.below(256).min(chars.len() - at);
+                    let copy = chars[at..at + len].to_vec();
+                    let to = rng.below(chars.len());
+                    chars.splice(to..to, copy);
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    #[test]
+    fn parses_sis_table_strictly() {
+        let parsed = HtmlParser { strict: true }
+            .parse(read_table().as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(parsed.db.semester.term, Some(202001));
+        assert_eq!(
+            parsed.db.semester.description.as_deref(),
+            Some("Spring 2020")
+        );
+        assert_eq!(parsed.db.semester.source, Some(SourceFormat::SisHtml));
+        let course = parsed
+            .db
+            .courses
+            .iter()
+            .find(|course| course.dept == "ADMN" && course.num == 1030)
+            .expect("ADMN 1030 is present");
+        assert_eq!(course.credits, Some(CreditRange { min: 0, max: 0 }));
+        let variable = parsed
+            .db
+            .courses
+            .iter()
+            .find(|course| course.dept == "CSCI" && course.num == 6980)
+            .expect("CSCI 6980 is present");
+        assert_eq!(variable.credits, Some(CreditRange { min: 1, max: 9 }));
+        let half_term = parsed
+            .db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .find(|section| section.crn == 91256)
+            .map(|section| &section.periods[0])
+            .expect("CRN 91256 has a period");
+        assert_eq!(half_term.date_start, CalendarDate::new(3, 9));
+        assert_eq!(half_term.date_end, CalendarDate::new(5, 8));
+        assert!(!half_term.meets_on(Day::Monday, CalendarDate::new(2, 3)));
+        assert!(half_term.meets_on(Day::Monday, CalendarDate::new(4, 6)));
+        assert!(half_term.meets_on(Day::Monday, None));
+        let attributed = parsed
+            .db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .find(|section| section.crn == 95241)
+            .expect("CRN 95241 is present");
+        assert_eq!(attributed.campus, Some(Campus::Troy));
+        assert_eq!(
+            attributed.attributes,
+            vec!["Communication Intensive", "Writing Intensive"]
+        );
+        assert!(attributed.has_attribute("ci"));
+        assert!(!attributed.has_attribute("HASS Inquiry"));
+        let honors = parsed
+            .db
+            .courses
+            .iter()
+            .find(|course| course.dept == "ADMN" && course.num == 6700)
+            .expect("ADMN 6700 is present");
+        assert_eq!(honors.sections.len(), 2);
+        assert_eq!(honors.sections[1].num, SectionId::from("H01"));
+        assert_eq!(honors.sections[1].crn, 94330);
+        let tba = &honors.sections[1].periods[0];
+        assert_eq!((tba.time, tba.location.as_deref()), (None, None));
+        assert!(tba.days.is_empty());
+        let team_taught = parsed
+            .db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .find(|section| section.crn == 91070)
+            .map(|section| &section.periods[0])
+            .expect("CRN 91070 has a period");
+        assert_eq!(
+            team_taught.instructors,
+            vec![
+                Instructor {
+                    name: "Zackary B. Davis".to_string(),
+                    primary: true
+                },
+                Instructor {
+                    name: "Jennifer Lynn Fredericks".to_string(),
+                    primary: false
+                }
+            ]
+        );
+        let section = &course.sections[0];
+        assert_eq!(section.crn, 95863);
+        let empty = SeatCount {
+            capacity: 0,
+            actual: 0,
+            remaining: 0,
+        };
+        assert_eq!(
+            section.seats,
+            Some(SeatCount {
+                capacity: 1200,
+                actual: 16,
+                remaining: 1184
+            })
+        );
+        assert_eq!(section.waitlist, Some(empty));
+        assert_eq!(section.crosslist, Some(empty));
+    }
+
+    #[test]
+    fn strict_rejects_what_lossy_skips() {
+        let input = table_prefix(20).replacen(">95863</a>", ">9586x</a>", 1);
+        let err = HtmlParser { strict: true }
+            .parse(input.as_str())
+            .expect_err("strict parse fails");
+        assert_eq!(err.field, Some("CRN"));
+        let parsed = HtmlParser { strict: false }
+            .parse(input.as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.warnings[0].location, err.location);
+        assert!(!parsed.db.courses.is_empty());
+    }
+
+    /// A minimal table with the columns in a different order than SIS uses.
+    fn reordered_table(headers: &[&str]) -> String {
+        let header = headers
+            .iter()
+            .map(|header| format!("<th class=\"ddheader\">{}</th>", header))
+            .collect::<String>();
+        let cells = |cells: &[&str]| {
+            cells
+                .iter()
+                .map(|cell| format!("<td>{}</td>", cell))
+                .collect::<String>()
+        };
+        format!(
+            "<table><tr>{}</tr><tr>{}</tr><tr>{}</tr></table>",
+            header,
+            cells(&[
+                "DCC 308",
+                "Staff (P)",
+                "10:00 am-11:50 am",
+                "MR",
+                "DATA STRUCTURES",
+                "01",
+                "1200",
+                "CSCI",
+                "<a>90001</a>",
+            ]),
+            cells(&[
+                "DCC 318",
+                "Staff (P)",
+                "4:00 pm-5:50 pm",
+                "W",
+                "DATA STRUCTURES",
+                "02",
+                "1200",
+                "CSCI",
+                "<a>90002</a>",
+            ]),
+        )
+    }
+
+    #[test]
+    fn malformed_rows_are_not_unscheduled() {
+        let input = reordered_table(REORDERED_HEADERS).replace("11:50 am", "11:5O am");
+        let parsed = HtmlParser { strict: false }
+            .parse(input.as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(parsed.warnings.len(), 1);
+        let crns = parsed
+            .db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .map(|section| section.crn)
+            .collect::<Vec<_>>();
+        assert_eq!(crns, [90002]);
+        assert!(parsed.db.find_unscheduled().is_empty());
+    }
+
+    const REORDERED_HEADERS: &[&str] = &[
+        "Location",
+        "Instructor",
+        "Time",
+        "Days",
+        "Title",
+        "<ABBR title = Section>Sec</ABBR>",
+        "<ABBR title = Course>Crse</ABBR>",
+        "<ABBR title = Subject>Subj</ABBR>",
+        "<ACRONYM title = \"Course Reference Number\">CRN</ACRONYM>",
+    ];
+
+    #[test]
+    fn columns_are_mapped_from_the_header() {
+        let input = reordered_table(REORDERED_HEADERS);
+        let db = HtmlParser { strict: true }
+            .parse(input.as_str())
+            .unwrap_or_else(|err| panic!("{}", err))
+            .db;
+        assert_eq!(db.courses.len(), 1);
+        let course = &db.courses[0];
+        assert_eq!((course.dept.as_str(), course.num), ("CSCI", 1200));
+        assert_eq!(course.name, "DATA STRUCTURES");
+        assert_eq!(course.sections.len(), 2);
+        let section = &course.sections[1];
+        assert_eq!((section.crn, &section.num), (90002, &SectionId::from("02")));
+        let period = &section.periods[0];
+        assert_eq!(period.location.as_deref(), Some("DCC 318"));
+        assert_eq!(period.days, vec![Day::Wednesday]);
+        assert_eq!(
+            period.time.map(|time| time.start),
+            Some(TimeCode::try_from(1600).unwrap())
+        );
+    }
+
+    #[test]
+    fn weekend_days_are_kept() {
+        let input = reordered_table(REORDERED_HEADERS).replace("<td>W</td>", "<td>SU</td>");
+        let db = HtmlParser { strict: true }
+            .parse(input.as_str())
+            .unwrap_or_else(|err| panic!("{}", err))
+            .db;
+        let period = &db.courses[0].sections[1].periods[0];
+        assert_eq!(period.days, vec![Day::Saturday, Day::Sunday]);
+    }
+
+    #[test]
+    fn unknown_days_are_skipped() {
+        let input = reordered_table(REORDERED_HEADERS).replace("<td>W</td>", "<td>WX</td>");
+        let err = HtmlParser { strict: true }
+            .parse(input.as_str())
+            .expect_err("strict parse fails");
+        assert_eq!(err.location, SourceLocation::TableRow(3));
+        assert_eq!(err.field, Some("Days"));
+        let parsed = HtmlParser { strict: false }
+            .parse(input.as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.warnings[0].location, err.location);
+        let period = &parsed.db.courses[0].sections[1].periods[0];
+        assert_eq!(period.days, vec![Day::Wednesday]);
+    }
+
+    #[test]
+    fn missing_columns_are_errors() {
+        let input = reordered_table(&REORDERED_HEADERS[1..]);
+        let err = HtmlParser { strict: true }
+            .parse(input.as_str())
+            .expect_err("strict parse fails");
+        assert_eq!(err.location, SourceLocation::TableRow(1));
+        assert_eq!(err.field, Some("Location"));
+        let parsed = HtmlParser { strict: false }
+            .parse(input.as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(parsed.db.courses.is_empty());
+        assert_eq!(parsed.warnings.len(), 3);
+    }
+
+    #[test]
+    fn truncated_rows_are_errors() {
+        let input = reordered_table(REORDERED_HEADERS).replace("<td>DCC 308</td>", "");
+        let err = HtmlParser { strict: true }
+            .parse(input.as_str())
+            .expect_err("strict parse fails");
+        assert_eq!(err.location, SourceLocation::TableRow(2));
+        assert!(HtmlParser { strict: false }.parse(input.as_str()).is_ok());
+    }
+
+    #[test]
+    fn mutated_tables_never_panic() {
+        let input = table_prefix(40);
+        let mut rng = Rng(0x5eed_1e55_c0ff_ee00);
+        for _ in 0..100 {
+            let mutated = mutate(input.as_str(), &mut rng);
+            let _ = HtmlParser { strict: true }.parse(mutated.as_str());
+            HtmlParser { strict: false }
+                .parse(mutated.as_str())
+                .expect("lossy parses never fail");
+        }
+    }
+}
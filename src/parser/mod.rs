@@ -1,19 +1,125 @@
-use crate::{parser::htmlparser::CourseDBHTMLParseError, CourseDB};
+use crate::CourseDB;
 use std::fmt::{Display, Formatter};
 
 mod htmlparser;
 
-pub use htmlparser::HtmlParser;
+pub use htmlparser::{CourseDBHTMLParseError, HtmlParser};
 
 #[cfg(feature = "rcosxml")]
 mod xml_parser;
 
+#[cfg(feature = "rcosxml")]
+pub use xml_parser::{CourseDBError, RcosXmlParser};
+
 pub trait CourseDBParser {
-    fn parse(&self, input: &str) -> Result<CourseDB, CourseDBParseError>;
+    /// Parses the input into a [`CourseDB`], along with the problems that were skipped over.
+    fn parse(&self, input: &str) -> Result<ParsedCourseDB, CourseDBParseError>;
+}
+
+/// The result of a successful parse.
+#[derive(Debug)]
+pub struct ParsedCourseDB {
+    /// The parsed database
+    pub db: CourseDB,
+
+    /// Problems encountered in the input that were skipped instead of failing the parse
+    pub warnings: Vec<CourseDBParseError>,
+}
+
+/// A problem in the input of a [`CourseDBParser`], along with where it occurred.
+#[derive(Debug)]
+pub struct CourseDBParseError {
+    /// Where in the input the problem is
+    pub location: SourceLocation,
+
+    /// The name of the field that failed to parse, if the problem is limited to one field
+    pub field: Option<&'static str>,
+
+    /// What went wrong
+    pub cause: ParseErrorCause,
 }
 
-pub enum CourseDBParseError {}
+impl CourseDBParseError {
+    pub fn new(
+        location: SourceLocation,
+        field: Option<&'static str>,
+        cause: impl Into<ParseErrorCause>,
+    ) -> Self {
+        Self {
+            location,
+            field,
+            cause: cause.into(),
+        }
+    }
+}
 
 impl Display for CourseDBParseError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "") }
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.field {
+            Some(field) => write!(f, "{}, field \"{}\": {}", self.location, field, self.cause),
+            None => write!(f, "{}: {}", self.location, self.cause),
+        }
+    }
+}
+
+impl std::error::Error for CourseDBParseError {}
+
+/// A position in the input of a [`CourseDBParser`]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SourceLocation {
+    /// The 1-based index of a row in the SIS table
+    TableRow(usize),
+
+    /// A 1-based line and column in an XML document
+    #[cfg(feature = "rcosxml")]
+    Xml { line: u32, column: u32 },
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TableRow(row) => write!(f, "Table row {}", row),
+            #[cfg(feature = "rcosxml")]
+            Self::Xml { line, column } => write!(f, "Line {}, column {}", line, column),
+        }
+    }
+}
+
+/// The underlying cause of a [`CourseDBParseError`]
+#[derive(Debug)]
+pub enum ParseErrorCause {
+    /// A problem with an SIS table row
+    Html(CourseDBHTMLParseError),
+    /// The input is not well-formed XML
+    #[cfg(feature = "rcosxml")]
+    Xml(roxmltree::Error),
+    /// The input is XML, but does not describe a valid RCOS course database
+    #[cfg(feature = "rcosxml")]
+    Rcos(CourseDBError),
+}
+
+impl Display for ParseErrorCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Html(err) => write!(f, "{}", err),
+            #[cfg(feature = "rcosxml")]
+            Self::Xml(err) => write!(f, "Malformed XML: {}", err),
+            #[cfg(feature = "rcosxml")]
+            Self::Rcos(err) => write!(f, "Invalid RCOS course database: {}", err),
+        }
+    }
+}
+
+impl From<CourseDBHTMLParseError> for ParseErrorCause {
+    fn from(err: CourseDBHTMLParseError) -> Self { Self::Html(err) }
+}
+
+#[cfg(feature = "rcosxml")]
+impl From<roxmltree::Error> for ParseErrorCause {
+    fn from(err: roxmltree::Error) -> Self { Self::Xml(err) }
+}
+
+#[cfg(feature = "rcosxml")]
+impl From<CourseDBError> for ParseErrorCause {
+    fn from(err: CourseDBError) -> Self { Self::Rcos(err) }
 }
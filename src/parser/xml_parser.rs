@@ -1,231 +1,611 @@
 //! A monolithic block of code that handles parsing of a ROCS xml file.
 
-use crate::{models, TimeCode};
+use crate::{
+    models,
+    parser::{CourseDBParseError, CourseDBParser, ParsedCourseDB, SourceLocation},
+    TimeCode,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
 use roxmltree::{Document, Node};
 use std::{num::ParseIntError, str::FromStr};
-use whereisclass::models;
 
-fn parse_day(day: Node) -> Result<models::Day, CourseDBError> {
-    models::Day::from_str(day.text()?).map_err(|a| a.into())
+lazy_static! {
+    static ref TIME_REGEX: Regex = Regex::new(r"^\d+$").unwrap();
+    static ref TBA_REGEX: Regex = Regex::new(r"^\**\s*TBA\s*\**$").unwrap();
 }
 
-fn parse_period(period_node: Node, strict: bool) -> Result<models::Period, CourseDBError> {
-    let time_regex = regex::Regex::new(r#"^\d+$"#).unwrap();
-    let time_start = period_node.attribute("start")?;
-    let time_end = period_node.attribute("end")?;
+/// Parses the RCOS XML course database.
+///
+/// In strict mode, the first malformed element fails the whole parse. Otherwise, malformed
+/// elements are skipped and returned as warnings.
+pub struct RcosXmlParser {
+    pub strict: bool,
+}
 
-    if !time_regex.is_match(time_start) || !time_regex.is_match(time_end) {
-        return Err(CourseDBError::MissingValue);
+impl CourseDBParser for RcosXmlParser {
+    fn parse(&self, input: &str) -> Result<ParsedCourseDB, CourseDBParseError> {
+        let doc = Document::parse(input).map_err(|err| {
+            let pos = err.pos();
+            CourseDBParseError::new(
+                SourceLocation::Xml {
+                    line: pos.row,
+                    column: pos.col,
+                },
+                None,
+                err,
+            )
+        })?;
+        let mut warnings = Vec::new();
+        self.parse_db(&doc, &mut warnings)
+            .map(|db| ParsedCourseDB { db, warnings })
     }
+}
 
-    let time_start = TimeCode::from_str(time_start)?;
-    let time_end = TimeCode::from_str(time_end)?;
+type Warnings = Vec<CourseDBParseError>;
 
-    let location = period_node.attribute("location").map(|s| s.to_string());
-    let period_type = period_node.attribute("type").map(|s| s.into());
+impl RcosXmlParser {
+    /// Handles a failure to parse a child element. Returns the error if it should be propagated.
+    fn reject(
+        &self,
+        err: CourseDBParseError,
+        warnings: &mut Warnings,
+    ) -> Result<(), CourseDBParseError> {
+        if self.strict {
+            Err(err)
+        } else {
+            warnings.push(err);
+            Ok(())
+        }
+    }
 
-    let instructor = period_node.attribute("instructor")?.to_string();
+    fn parse_day(&self, day: Node) -> Result<models::Day, CourseDBParseError> {
+        let text = day
+            .text()
+            .ok_or_else(|| error(day, "DAY", CourseDBError::MissingValue))?;
+        models::Day::from_str(text).map_err(|_| error(day, "DAY", CourseDBError::ParsingNum))
+    }
 
-    let mut period = models::Period {
-        time_start,
-        time_end,
-        instructor,
-        location,
-        period_type,
-        days: Vec::new(),
-    };
+    /// Parses a period. Periods whose times are still to be announced are not errors, but have no
+    /// time.
+    fn parse_period(
+        &self,
+        period_node: Node,
+        warnings: &mut Warnings,
+    ) -> Result<models::Period, CourseDBParseError> {
+        let time_start = attribute(period_node, "start")?;
+        let time_end = attribute(period_node, "end")?;
 
-    for day in period_node
-        .children()
-        .filter(|child| child.tag_name().name() == "DAY")
-    {
-        let res = parse_day(day);
-        if res.is_err() {
-            let err = res.unwrap_err();
-            if VERBOSE {
-                match err {
-                    CourseDBError::ParsingNum => eprintln!(
-                        "Failed to parse day due to malformed number, XML: {:?}",
-                        day
-                    ),
-                    CourseDBError::MissingValue => {
-                        eprintln!("Failed to parse day due to empty value, XML: {:?}", day)
-                    }
-                }
-            }
-            if strict {
-                return Err(err);
-            }
+        let time = if TBA_REGEX.is_match(time_start) || TBA_REGEX.is_match(time_end) {
+            None
         } else {
-            period.days.push(res.unwrap());
+            Some(models::TimeRange {
+                start: time_attribute(period_node, "start", time_start)?,
+                end: time_attribute(period_node, "end", time_end)?,
+            })
+        };
+
+        let location = period_node
+            .attribute("location")
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.to_string());
+        let period_type = period_node.attribute("type").map(|s| s.into());
+
+        // Co-instructors are separated by slashes, with the primary instructor listed first
+        let instructors = attribute(period_node, "instructor")?
+            .split('/')
+            .enumerate()
+            .filter_map(|(idx, name)| models::Instructor::new(name, idx == 0))
+            .collect();
+
+        let mut period = models::Period {
+            time,
+            instructors,
+            location,
+            period_type,
+            days: Vec::new(),
+            date_start: None,
+            date_end: None,
+        };
+
+        for day in period_node
+            .children()
+            .filter(|child| child.tag_name().name() == "DAY")
+        {
+            match self.parse_day(day) {
+                Ok(day) => period.days.push(day),
+                Err(err) => self.reject(err, warnings)?,
+            }
         }
+        Ok(period)
     }
-    Ok(period)
-}
 
-fn parse_note(note: Node) -> Result<String, CourseDBError> {
-    let note = note.text()?.to_string();
-    Ok(note)
-}
+    fn parse_note(&self, note: Node) -> Result<String, CourseDBParseError> {
+        let note = note
+            .text()
+            .ok_or_else(|| error(note, "NOTE", CourseDBError::MissingValue))?
+            .to_string();
+        Ok(note)
+    }
 
-fn parse_section(section_node: Node, strict: bool) -> Result<models::Section, CourseDBError> {
-    let crn = section_node.attribute("crn")?;
-    let crn = u32::from_str(crn)?;
-    let num = section_node.attribute("num")?;
-    let num = u8::from_str(num)?;
-
-    let mut section = models::Section {
-        crn,
-        num,
-        periods: Vec::new(),
-        notes: Vec::new(),
-    };
+    fn parse_section(
+        &self,
+        section_node: Node,
+        warnings: &mut Warnings,
+    ) -> Result<models::Section, CourseDBParseError> {
+        let crn = numeric_attribute(section_node, "crn")?;
+        let num = models::SectionId::from(attribute(section_node, "num")?);
+        let students = optional_numeric_attribute(section_node, "students")?;
+        let seats = optional_numeric_attribute(section_node, "seats")?;
 
-    for period in section_node
-        .children()
-        .filter(|child| child.tag_name().name() == "PERIOD")
-    {
-        let res = parse_period(period, strict);
-        if res.is_err() {
-            let err = res.unwrap_err();
-            if VERBOSE {
-                match err {
-                    CourseDBError::ParsingNum => eprintln!(
-                        "Failed to parse period due to malformed number, XML: {:?}",
-                        period
-                    ),
-                    CourseDBError::MissingValue => eprintln!(
-                        "Failed to parse period due to empty value, XML: {:?}",
-                        period
-                    ),
-                }
-            }
-            if strict {
-                return Err(err);
+        let mut section = models::Section {
+            crn,
+            num,
+            periods: Vec::new(),
+            notes: Vec::new(),
+            seats: seats
+                .zip(students)
+                .map(|(capacity, actual): (u32, u32)| models::SeatCount {
+                    capacity,
+                    actual,
+                    remaining: capacity as i32 - actual as i32,
+                }),
+            waitlist: None,
+            crosslist: None,
+            campus: None,
+            attributes: Vec::new(),
+        };
+
+        // A malformed period rejects the whole section, so that a section missing some of its
+        // meetings is never mistaken for an unscheduled one
+        for period in section_node
+            .children()
+            .filter(|child| child.tag_name().name() == "PERIOD")
+        {
+            section.periods.push(self.parse_period(period, warnings)?);
+        }
+
+        for note in section_node
+            .children()
+            .filter(|course_child| course_child.tag_name().name() == "NOTE")
+        {
+            match self.parse_note(note) {
+                Ok(note) => section.notes.push(note),
+                Err(err) => self.reject(err, warnings)?,
             }
-        } else {
-            section.periods.push(res.unwrap());
         }
+
+        Ok(section)
     }
 
-    for note in section_node
-        .children()
-        .filter(|course_child| course_child.tag_name().name() == "NOTE")
-    {
-        let res = parse_note(note);
-        if res.is_err() {
-            let err = res.unwrap_err();
-            match err {
-                CourseDBError::ParsingNum => eprintln!(
-                    "Failed to parse note due to malformed number, XML: {:?}",
-                    note
-                ),
-                CourseDBError::MissingValue => {
-                    eprintln!("Failed to parse note due to empty value, XML: {:?}", note)
-                }
+    fn parse_course(
+        &self,
+        course_node: Node,
+        warnings: &mut Warnings,
+    ) -> Result<models::Course, CourseDBParseError> {
+        let name = attribute(course_node, "name")?.to_string();
+        let dept = attribute(course_node, "dept")?.to_string();
+        let num = numeric_attribute(course_node, "num")?;
+        let credmin = optional_numeric_attribute(course_node, "credmin")?;
+        let credmax = optional_numeric_attribute(course_node, "credmax")?;
+        // Courses graded normally have an empty grade type
+        let grade_type = course_node.attribute("gradetype").map(|grade_type| {
+            if grade_type.trim().is_empty() {
+                models::GradeType::Letter
+            } else {
+                models::GradeType::from_str(grade_type)
+                    .unwrap_or_else(|_| models::GradeType::Other(grade_type.to_string()))
             }
-            if strict {
-                return Err(err);
+        });
+
+        let mut course = models::Course {
+            name,
+            dept,
+            num,
+            sections: Vec::new(),
+            credits: credmin
+                .zip(credmax)
+                .map(|(min, max)| models::CreditRange { min, max }),
+            grade_type,
+        };
+
+        for section in course_node
+            .children()
+            .filter(|course_child| course_child.tag_name().name() == "SECTION")
+        {
+            match self.parse_section(section, warnings) {
+                Ok(section) => course.sections.push(section),
+                Err(err) => self.reject(err, warnings)?,
             }
-        } else {
-            section.notes.push(res.unwrap());
         }
+
+        Ok(course)
     }
 
-    Ok(section)
-}
+    fn parse_crosslisting(
+        &self,
+        crosslisting_node: Node,
+    ) -> Result<models::Crosslisting, CourseDBParseError> {
+        let seats = numeric_attribute(crosslisting_node, "seats")?;
+        let crns = crosslisting_node
+            .children()
+            .filter(|child| child.tag_name().name() == "CRN")
+            .map(|crn| {
+                let text = crn
+                    .text()
+                    .ok_or_else(|| error(crn, "CRN", CourseDBError::MissingValue))?;
+                u32::from_str(text.trim()).map_err(|err| error(crn, "CRN", err.into()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(models::Crosslisting { seats, crns })
+    }
 
-fn parse_course(course_node: Node, strict: bool) -> Result<models::Course, CourseDBError> {
-    let name = course_node.attribute("name")?.to_string();
-    let dept = course_node.attribute("dept")?.to_string();
-    let num = course_node.attribute("num")?;
-    let num = u16::from_str(num)?;
-
-    let mut course = models::Course {
-        name,
-        dept,
-        num,
-        sections: Vec::new(),
-    };
+    fn parse_db(
+        &self,
+        doc: &Document,
+        warnings: &mut Warnings,
+    ) -> Result<models::CourseDB, CourseDBParseError> {
+        let mut courses = Vec::<models::Course>::new();
+        let mut crosslistings = Vec::<models::Crosslisting>::new();
 
-    for section in course_node
-        .children()
-        .filter(|course_child| course_child.tag_name().name() == "SECTION")
-    {
-        let res = parse_section(section, strict);
-        if res.is_err() {
-            let err = res.unwrap_err();
-            if VERBOSE {
-                match err {
-                    CourseDBError::ParsingNum => eprintln!(
-                        "Failed to parse section due to malformed number, XML: {:?}",
-                        section
-                    ),
-                    CourseDBError::MissingValue => eprintln!(
-                        "Failed to parse section due to empty value, XML: {:?}",
-                        section
-                    ),
-                }
+        for crosslisting in doc
+            .root_element()
+            .children()
+            .filter(|node| node.tag_name().name() == "CROSSLISTING")
+        {
+            match self.parse_crosslisting(crosslisting) {
+                Ok(crosslisting) => crosslistings.push(crosslisting),
+                Err(err) => self.reject(err, warnings)?,
             }
-            if strict {
-                return Err(err);
+        }
+
+        for course in doc
+            .root_element()
+            .children()
+            .filter(|node| node.tag_name().name() == "COURSE")
+        {
+            match self.parse_course(course, warnings) {
+                Ok(course) => courses.push(course),
+                Err(err) => self.reject(err, warnings)?,
             }
-        } else {
-            course.sections.push(res.unwrap())
         }
+        let root = doc.root_element();
+        let semester = models::Semester {
+            term: optional_numeric_attribute(root, "semesternumber")?,
+            description: root
+                .attribute("semesterdesc")
+                .map(str::trim)
+                .filter(|description| !description.is_empty())
+                .map(|description| description.to_string()),
+            source: Some(models::SourceFormat::RcosXml),
+            timestamp: optional_numeric_attribute(root, "timestamp")?,
+        };
+        Ok(models::CourseDB {
+            semester,
+            courses,
+            crosslistings,
+        })
     }
+}
 
-    Ok(course)
+/// Builds an error located at the start of the given node.
+fn error(node: Node, field: &'static str, cause: CourseDBError) -> CourseDBParseError {
+    let pos = node.document().text_pos_at(node.range().start);
+    CourseDBParseError::new(
+        SourceLocation::Xml {
+            line: pos.row,
+            column: pos.col,
+        },
+        Some(field),
+        cause,
+    )
 }
 
-pub fn parse_db<S: AsRef<str>>(xml: S, strict: bool) -> Result<models::CourseDB, CourseDBError> {
-    let mut courses = Vec::<models::Course>::new();
-
-    let doc = Document::parse(xml.as_ref()).expect("Parsing XML");
-    for course in doc
-        .root_element()
-        .children()
-        .filter(|node| node.tag_name().name() == "COURSE")
-    {
-        let res = parse_course(course, strict);
-        if res.is_err() {
-            let err = res.unwrap_err();
-            if VERBOSE {
-                match err {
-                    CourseDBError::ParsingNum => eprintln!(
-                        "Failed to parse course due to malformed number, XML: {:?}",
-                        course
-                    ),
-                    CourseDBError::MissingValue => eprintln!(
-                        "Failed to parse course due to empty value, XML: {:?}",
-                        course
-                    ),
-                }
-            }
-            return Err(err);
-        } else {
-            courses.push(res.unwrap());
-        }
+fn attribute<'a>(node: Node<'a, '_>, name: &'static str) -> Result<&'a str, CourseDBParseError> {
+    node.attribute(name)
+        .ok_or_else(|| error(node, name, CourseDBError::MissingValue))
+}
+
+fn numeric_attribute<T: FromStr<Err = ParseIntError>>(
+    node: Node,
+    name: &'static str,
+) -> Result<T, CourseDBParseError> {
+    T::from_str(attribute(node, name)?).map_err(|err| error(node, name, err.into()))
+}
+
+fn optional_numeric_attribute<T: FromStr<Err = ParseIntError>>(
+    node: Node,
+    name: &'static str,
+) -> Result<Option<T>, CourseDBParseError> {
+    node.attribute(name)
+        .map(|value| T::from_str(value).map_err(|err| error(node, name, err.into())))
+        .transpose()
+}
+
+fn time_attribute(
+    node: Node,
+    name: &'static str,
+    value: &str,
+) -> Result<TimeCode, CourseDBParseError> {
+    if !TIME_REGEX.is_match(value) {
+        return Err(error(node, name, CourseDBError::MissingValue));
     }
-    Ok(models::CourseDB { courses })
+    TimeCode::from_str(value).map_err(|_| error(node, name, CourseDBError::InvalidTime))
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum CourseDBError {
     ParsingNum,
     MissingValue,
+    InvalidTime,
 }
 
 impl std::error::Error for CourseDBError {}
 
 impl std::fmt::Display for CourseDBError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { write!(f, "{:?}", self) }
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ParsingNum => write!(f, "malformed number"),
+            Self::MissingValue => write!(f, "empty value"),
+            Self::InvalidTime => write!(f, "invalid time"),
+        }
+    }
 }
 
 impl From<ParseIntError> for CourseDBError {
     fn from(_: ParseIntError) -> Self { Self::ParsingNum }
 }
 
-impl From<std::option::NoneError> for CourseDBError {
-    fn from(_: std::option::NoneError) -> Self { Self::MissingValue }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::FindUnscheduled,
+        models::{
+            CreditRange, Crosslisting, Day, GradeType, PeriodType, SeatCount, SectionId, Semester,
+            SourceFormat, TimeRange,
+        },
+    };
+    use std::fs;
+
+    fn read_input(name: &str) -> String {
+        fs::read_to_string(format!("{}/raw-input/{}", env!("CARGO_MANIFEST_DIR"), name))
+            .expect("Reading raw input")
+    }
+
+    #[test]
+    fn parses_fall_2019() {
+        let parsed = RcosXmlParser { strict: true }
+            .parse(read_input("201909.xml").as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(parsed.warnings.is_empty());
+        let db = parsed.db;
+        assert_eq!(
+            db.semester,
+            Semester {
+                term: Some(201909),
+                description: Some("Fall 2019".to_string()),
+                source: Some(SourceFormat::RcosXml),
+                timestamp: Some(1570528802),
+            }
+        );
+        let course = db
+            .courses
+            .iter()
+            .find(|course| course.dept == "ADMN" && course.num == 1010)
+            .expect("ADMN 1010 is present");
+        assert_eq!(course.name, "EFF COMM FOR CLASS PEDAGOGY");
+        assert_eq!(course.credits, Some(CreditRange { min: 0, max: 0 }));
+        assert_eq!(
+            course.grade_type,
+            Some(GradeType::SatisfactoryUnsatisfactory)
+        );
+        let section = &course.sections[0];
+        assert_eq!(section.crn, 81273);
+        assert_eq!(section.num, SectionId::from("01"));
+        let period = &section.periods[0];
+        assert_eq!(
+            period.time,
+            Some(TimeRange {
+                start: TimeCode::try_from(1400).unwrap(),
+                end: TimeCode::try_from(1550).unwrap()
+            })
+        );
+        assert_eq!(period.days, vec![Day::Monday, Day::Thursday]);
+        assert_eq!(period.location.as_deref(), Some("SAGE 3205"));
+        assert_eq!(period.period_type, Some(PeriodType::Lecture));
+        assert!(period.instructors.is_empty());
+        let team_taught = db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .find(|section| section.crn == 83572)
+            .map(|section| &section.periods[0])
+            .expect("CRN 83572 has a period");
+        assert_eq!(
+            team_taught
+                .instructors
+                .iter()
+                .map(|instructor| (instructor.name.as_str(), instructor.primary))
+                .collect::<Vec<_>>(),
+            vec![("Marchewka", true), ("Pertierra", false)]
+        );
+        let seats = db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .find(|section| section.crn == 85575)
+            .and_then(|section| section.seats)
+            .expect("CRN 85575 has seat counts");
+        assert_eq!(
+            seats,
+            SeatCount {
+                capacity: 1000,
+                actual: 165,
+                remaining: 835
+            }
+        );
+        let lettered = db
+            .courses
+            .iter()
+            .flat_map(|course| course.sections.iter())
+            .find(|section| section.crn == 84500)
+            .expect("CRN 84500 is present");
+        assert_eq!(lettered.num, SectionId::from("T01"));
+        assert_eq!(db.crosslistings.len(), 177);
+        assert_eq!(
+            db.crosslistings[0],
+            Crosslisting {
+                seats: 16,
+                crns: vec![82920, 84110]
+            }
+        );
+    }
+
+    #[test]
+    fn parses_spring_2020() {
+        let parsed = RcosXmlParser { strict: true }
+            .parse(read_input("202001.xml").as_str())
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert!(parsed.warnings.is_empty());
+        let db = parsed.db;
+        assert!(db.courses.len() > 1000);
+        assert!(db
+            .courses
+            .iter()
+            .any(|course| course.dept == "CSCI" && course.num == 1200));
+    }
+
+    #[test]
+    fn strict_parses_well_formed_input() {
+        let input = r#"<CourseDB>
+            <COURSE name="GOOD" dept="TEST" num="1000">
+                <SECTION crn="10000" num="01">
+                    <PERIOD type="LAB" instructor="Staff" start="0900" end="0950" location="DCC 308">
+                        <DAY>1</DAY>
+                    </PERIOD>
+                    <PERIOD type="LEC" instructor="Staff" start="** TBA **" end="** TBA **" location=" " />
+                    <NOTE>Note</NOTE>
+                </SECTION>
+            </COURSE>
+        </CourseDB>"#;
+        let db = RcosXmlParser { strict: true }
+            .parse(input)
+            .unwrap_or_else(|err| panic!("{}", err))
+            .db;
+        let section = &db.courses[0].sections[0];
+        assert_eq!(section.periods.len(), 2);
+        assert_eq!(section.periods[0].days, vec![Day::Tuesday]);
+        assert_eq!(section.periods[0].period_type, Some(PeriodType::Lab));
+        assert_eq!(section.notes, vec!["Note".to_string()]);
+    }
+
+    #[test]
+    fn tba_periods_are_kept() {
+        let input = r#"<CourseDB>
+            <COURSE name="GOOD" dept="TEST" num="1000">
+                <SECTION crn="10000" num="01">
+                    <PERIOD type="SEM" instructor="Bruce" start="** TBA **" end="** TBA **" location=" " />
+                </SECTION>
+            </COURSE>
+        </CourseDB>"#;
+        let db = RcosXmlParser { strict: true }
+            .parse(input)
+            .unwrap_or_else(|err| panic!("{}", err))
+            .db;
+        let section = &db.courses[0].sections[0];
+        assert_eq!(section.periods.len(), 1);
+        let period = &section.periods[0];
+        assert_eq!(period.time, None);
+        assert_eq!(period.location, None);
+        assert!(!period.is_scheduled());
+    }
+
+    #[test]
+    fn strict_rejects_what_lossy_skips() {
+        let input = r#"<CourseDB>
+            <COURSE name="GOOD" dept="TEST" num="1000">
+                <SECTION crn="10000" num="01" />
+            </COURSE>
+            <COURSE name="BAD" dept="TEST" num="not a number" />
+        </CourseDB>"#;
+        let err = RcosXmlParser { strict: true }
+            .parse(input)
+            .expect_err("strict parse fails");
+        assert_eq!(
+            err.location,
+            SourceLocation::Xml {
+                line: 5,
+                column: 13
+            }
+        );
+        assert_eq!(err.field, Some("num"));
+        let parsed = RcosXmlParser { strict: false }
+            .parse(input)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(parsed.db.courses.len(), 1);
+        assert_eq!(parsed.db.courses[0].name, "GOOD");
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.warnings[0].location, err.location);
+    }
+
+    #[test]
+    fn malformed_periods_are_not_unscheduled() {
+        let input = r#"<CourseDB>
+            <COURSE name="GOOD" dept="TEST" num="1000">
+                <SECTION crn="10000" num="01">
+                    <PERIOD type="LEC" instructor="Staff" start="1000" end="11:5O" location="DCC 308">
+                        <DAY>0</DAY>
+                    </PERIOD>
+                </SECTION>
+                <SECTION crn="10001" num="02">
+                    <PERIOD type="LEC" instructor="Staff" start="1000" end="1150" location="DCC 308">
+                        <DAY>1</DAY>
+                    </PERIOD>
+                </SECTION>
+            </COURSE>
+        </CourseDB>"#;
+        let parsed = RcosXmlParser { strict: false }
+            .parse(input)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(parsed.warnings.len(), 1);
+        let crns = parsed.db.courses[0]
+            .sections
+            .iter()
+            .map(|section| section.crn)
+            .collect::<Vec<_>>();
+        assert_eq!(crns, [10001]);
+        assert!(parsed.db.find_unscheduled().is_empty());
+    }
+
+    #[test]
+    fn unknown_days_are_skipped() {
+        let input = r#"<CourseDB>
+            <COURSE name="GOOD" dept="TEST" num="1000">
+                <SECTION crn="10000" num="01">
+                    <PERIOD type="LEC" instructor="Staff" start="1000" end="1150" location="DCC 308">
+                        <DAY>0</DAY>
+                        <DAY>9</DAY>
+                    </PERIOD>
+                </SECTION>
+            </COURSE>
+        </CourseDB>"#;
+        let err = RcosXmlParser { strict: true }
+            .parse(input)
+            .expect_err("strict parse fails");
+        assert_eq!(err.field, Some("DAY"));
+        let parsed = RcosXmlParser { strict: false }
+            .parse(input)
+            .unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(parsed.warnings.len(), 1);
+        let period = &parsed.db.courses[0].sections[0].periods[0];
+        assert_eq!(period.days, vec![models::Day::Monday]);
+    }
+
+    #[test]
+    fn malformed_xml_is_an_error() {
+        assert!(RcosXmlParser { strict: false }
+            .parse("<CourseDB><COURSE></CourseDB>")
+            .is_err());
+    }
 }
@@ -0,0 +1,283 @@
+//! Versioning of serialized course DBs, so that files written by older versions of whereisclass
+//! keep loading as the models change.
+
+use crate::models::{CourseDB, Instructor};
+use serde_derive::Serialize;
+use serde_json::{json, Map, Value};
+use std::{
+    fmt::{Display, Formatter},
+    io::{Read, Write},
+};
+
+/// The schema version written by this version of whereisclass. Whenever a model change would
+/// break reading older files, bump it and add the step upgrading the previous version to
+/// [`MIGRATIONS`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The key holding the schema version. Files without it predate versioning, and are version 0.
+const VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a serialized course DB by one version, so that `MIGRATIONS[n]` upgrades version `n`
+/// to version `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SCHEMA_VERSION as usize] = [migrate_v0];
+
+/// A course DB as it is written, with its schema version alongside the rest of the fields
+#[derive(Serialize)]
+struct VersionedCourseDB<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    db: &'a CourseDB,
+}
+
+/// Writes a course DB as JSON, tagged with the current schema version.
+pub fn to_writer(writer: impl Write, db: &CourseDB) -> serde_json::Result<()> {
+    serde_json::to_writer(
+        writer,
+        &VersionedCourseDB {
+            schema_version: SCHEMA_VERSION,
+            db,
+        },
+    )
+}
+
+/// Reads a course DB from JSON written with any schema version up to the current one, along
+/// with the version it was written with.
+pub fn from_reader(reader: impl Read) -> Result<(CourseDB, u32), SchemaError> {
+    let mut value = serde_json::from_reader(reader).map_err(SchemaError::Json)?;
+    let version = migrate(&mut value)?;
+    let db = serde_json::from_value(value).map_err(SchemaError::Json)?;
+    Ok((db, version))
+}
+
+/// Upgrades a serialized course DB to the current schema version in place, and returns the
+/// version it was at before.
+pub fn migrate(value: &mut Value) -> Result<u32, SchemaError> {
+    let object = value.as_object_mut().ok_or(SchemaError::NotAnObject)?;
+    let version = match object.get(VERSION_KEY) {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| SchemaError::InvalidVersion(version.clone()))?,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(SchemaError::TooNew(version));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(object);
+    }
+    object.insert(VERSION_KEY.to_string(), json!(SCHEMA_VERSION));
+    Ok(version)
+}
+
+/// The objects in the array under `key`, skipping anything that is not an object
+fn objects_mut<'a>(
+    object: &'a mut Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = &'a mut Map<String, Value>> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+/// Version 0 stored section numbers as integers, a single `instructor` per period with
+/// co-instructors separated by slashes, and unscheduled rooms as "TBA". Some version 0 files also
+/// read times like "12:30 pm" as 2430.
+fn migrate_v0(db: &mut Map<String, Value>) {
+    for course in objects_mut(db, "courses") {
+        for section in objects_mut(course, "sections") {
+            if let Some(num) = section.get("num").and_then(Value::as_u64) {
+                section.insert("num".to_string(), json!(format!("{:02}", num)));
+            }
+            for period in objects_mut(section, "periods") {
+                let instructors = match period.remove("instructor") {
+                    // The primary instructor is listed first, like in RCOS XML files
+                    Some(Value::String(names)) => names
+                        .split('/')
+                        .enumerate()
+                        .filter_map(|(idx, name)| Instructor::new(name, idx == 0))
+                        .map(|instructor| {
+                            json!({"name": instructor.name, "primary": instructor.primary})
+                        })
+                        .collect(),
+                    _ => vec![],
+                };
+                period.insert("instructors".to_string(), Value::Array(instructors));
+                let location = period
+                    .get("location")
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|location| !location.is_empty() && *location != "TBA")
+                    .map(|location| json!(location));
+                period.insert("location".to_string(), location.unwrap_or(Value::Null));
+                for key in ["time_start", "time_end"] {
+                    if let Some(time) = period
+                        .get(key)
+                        .and_then(Value::as_u64)
+                        .filter(|time| (2400..2460).contains(time))
+                    {
+                        period.insert(key.to_string(), json!(time - 1200));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A problem reading a serialized course DB
+#[derive(Debug)]
+pub enum SchemaError {
+    /// The file is not valid JSON, or does not describe a course DB after migrating
+    Json(serde_json::Error),
+    /// The file is valid JSON, but not an object
+    NotAnObject,
+    /// The schema version is not a non-negative integer
+    InvalidVersion(Value),
+    /// The file was written by a newer version of whereisclass
+    TooNew(u32),
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "{}", err),
+            Self::NotAnObject => write!(f, "Expected a JSON object"),
+            Self::InvalidVersion(version) => write!(f, "Invalid schema version {}", version),
+            Self::TooNew(version) => write!(
+                f,
+                "Schema version {} is newer than the supported version {}, update whereisclass \
+                 to read it",
+                version, SCHEMA_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Reads one of the course DBs bundled in `course-dbs`, along with its schema version
+#[cfg(test)]
+pub(crate) fn bundled_db(name: &str) -> (CourseDB, u32) {
+    let file = std::fs::File::open(format!(
+        "{}/course-dbs/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ))
+    .expect("Opening course DB");
+    from_reader(std::io::BufReader::new(file)).unwrap_or_else(|err| panic!("{}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SectionId;
+
+    const VERSION_0: &str = r#"{
+        "courses": [{
+            "name": "DATA STRUCTURES",
+            "dept": "CSCI",
+            "num": 1200,
+            "sections": [{
+                "crn": 90001,
+                "num": 1,
+                "periods": [{
+                    "time_start": 1000,
+                    "time_end": 1150,
+                    "instructor": "Barbara   Cutler",
+                    "days": ["Tuesday", "Friday"],
+                    "location": "TBA",
+                    "period_type": null
+                }, {
+                    "time_start": 2400,
+                    "time_end": 1350,
+                    "instructor": "Kruger/Aboul-Seoud",
+                    "days": ["Wednesday"],
+                    "location": "DCC 318",
+                    "period_type": "Lab"
+                }],
+                "notes": []
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn version_0_is_migrated() {
+        let (db, version) =
+            from_reader(VERSION_0.as_bytes()).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(version, 0);
+        let section = &db.courses[0].sections[0];
+        assert_eq!(section.num, SectionId::from("01"));
+        let lecture = &section.periods[0];
+        assert_eq!(
+            lecture.instructors,
+            vec![Instructor {
+                name: "Barbara Cutler".to_string(),
+                primary: true
+            }]
+        );
+        assert_eq!(lecture.location, None);
+        let lab = &section.periods[1];
+        assert_eq!(
+            lab.instructors,
+            vec![
+                Instructor {
+                    name: "Kruger".to_string(),
+                    primary: true
+                },
+                Instructor {
+                    name: "Aboul-Seoud".to_string(),
+                    primary: false
+                }
+            ]
+        );
+        assert_eq!(
+            lab.time
+                .map(|time| (u16::from(time.start), u16::from(time.end))),
+            Some((1200, 1350))
+        );
+        assert_eq!(lab.location.as_deref(), Some("DCC 318"));
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let (db, _) = from_reader(VERSION_0.as_bytes()).unwrap_or_else(|err| panic!("{}", err));
+        let mut written = Vec::new();
+        to_writer(&mut written, &db).unwrap();
+        let (read, version) =
+            from_reader(written.as_slice()).unwrap_or_else(|err| panic!("{}", err));
+        assert_eq!(version, SCHEMA_VERSION);
+        assert_eq!(read, db);
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let input = format!(
+            r#"{{"schema_version": {}, "courses": []}}"#,
+            SCHEMA_VERSION + 1
+        );
+        assert!(matches!(
+            from_reader(input.as_bytes()),
+            Err(SchemaError::TooNew(version)) if version == SCHEMA_VERSION + 1
+        ));
+        assert!(matches!(
+            from_reader(r#"{"schema_version": "1"}"#.as_bytes()),
+            Err(SchemaError::InvalidVersion(_))
+        ));
+        assert!(matches!(
+            from_reader("[]".as_bytes()),
+            Err(SchemaError::NotAnObject)
+        ));
+    }
+
+    #[test]
+    fn bundled_course_dbs_load() {
+        for name in ["201909.json", "202001.json"] {
+            let (db, version) = bundled_db(name);
+            assert_eq!(version, 0);
+            assert!(db.courses.len() > 800);
+        }
+    }
+}